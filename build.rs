@@ -1,5 +1,5 @@
 use std::{
-  collections::BTreeMap,
+  collections::{BTreeMap, BTreeSet},
   env, fmt,
   fs::File,
   io::{self, BufReader, BufWriter, Write},
@@ -46,15 +46,38 @@ fn output_file(file_name: &str) -> io::Result<BufWriter<File>> {
   Ok(BufWriter::new(File::create(path)?))
 }
 
+/// Cultures for which translations are generated, the first one is used as fallback.
+const CULTURES: &[&str] = &["en", "de"];
+
+/// A translation, either only in English or in all available cultures.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Translation {
+  En(String),
+  Cultures(BTreeMap<String, String>),
+}
+
+impl Translation {
+  /// Returns the text in the given culture, falling back to English.
+  fn get(&self, culture: &str) -> &str {
+    match self {
+      Self::En(text) => text,
+      Self::Cultures(texts) => texts.get(culture).or_else(|| texts.get(CULTURES[0])).map_or("", String::as_str),
+    }
+  }
+}
+
 fn generate_translations() -> anyhow::Result<()> {
   println!("Generating translations.");
 
-  let translations: BTreeMap<u16, String> = load_json("translations.used.json")?;
+  let translations: BTreeMap<u16, Translation> = load_json("translations.used.json")?;
 
   let mut file = output_file("translations.rs")?;
 
   for (k, v) in translations {
-    writeln!(file, "const TRANSLATION_{}: &str = {:?};", k, v)?;
+    for culture in CULTURES {
+      writeln!(file, "pub const TRANSLATION_{}_{}: &str = {:?};", k, culture.to_uppercase(), v.get(culture))?;
+    }
   }
 
   Ok(())
@@ -70,13 +93,19 @@ fn generate_mappings() -> anyhow::Result<()> {
   writeln!(file, r#"include!(concat!(env!("OUT_DIR"), "/translations.rs"));"#)?;
 
   for (k, mapping) in mappings {
-    let mut map = phf_codegen::Map::new();
+    writeln!(file, "\npub const MAPPING_{}: crate::Mapping = crate::Mapping {{", k)?;
 
-    for (k, v) in mapping {
-      map.entry(k, format!("TRANSLATION_{}", v));
+    for culture in CULTURES {
+      let mut map = phf_codegen::Map::new();
+
+      for (k, v) in &mapping {
+        map.entry(*k, format!("TRANSLATION_{}_{}", v, culture.to_uppercase()));
+      }
+
+      writeln!(file, "  {}: {},", culture, map.build())?;
     }
 
-    writeln!(file, "\npub const MAPPING_{}: ::phf::Map<i32, &'static str> = {};", k, map.build())?;
+    writeln!(file, "}};")?;
   }
 
   Ok(())
@@ -194,6 +223,283 @@ fn generate_devices(command_name_map: &BTreeMap<u16, String>) -> anyhow::Result<
   Ok(())
 }
 
+/// Type names for units, unknown units are represented as plain `f64`.
+const UNITS: &[(&str, &str)] = &[
+  ("%", "Percent"),
+  ("%/K", "PercentPerKelvin"),
+  ("A", "Amperes"),
+  ("K", "Kelvin"),
+  ("K/h", "KelvinPerHour"),
+  ("K/min", "KelvinPerMinute"),
+  ("K/s", "KelvinPerSecond"),
+  ("Hz", "Hertz"),
+  ("MJ", "Megajoules"),
+  ("MWh", "MegawattHours"),
+  ("V", "Volts"),
+  ("W", "Watts"),
+  ("Wh", "WattHours"),
+  ("bar", "Bar"),
+  ("bara", "BarAbsolute"),
+  ("c/kWh", "CentsPerKilowattHour"),
+  ("d", "Days"),
+  ("dBm", "DecibelMilliwatts"),
+  ("h", "Hours"),
+  ("kW", "Kilowatts"),
+  ("kWh", "KilowattHours"),
+  ("kWh/m³", "KilowattHoursPerCubicMeter"),
+  ("kg", "Kilograms"),
+  ("kg/h", "KilogramsPerHour"),
+  ("l", "Liters"),
+  ("l/h", "LitersPerHour"),
+  ("l/min", "LitersPerMinute"),
+  ("m³", "CubicMeters"),
+  ("m³/h", "CubicMetersPerHour"),
+  ("mV", "Millivolts"),
+  ("mbar", "Millibar"),
+  ("min", "Minutes"),
+  ("mo", "Months"),
+  ("ppm", "PartsPerMillion"),
+  ("rev/min", "RevolutionsPerMinute"),
+  ("rev/s", "RevolutionsPerSecond"),
+  ("rev/s²", "RevolutionsPerSecondSquared"),
+  ("s", "Seconds"),
+  ("t", "Tonnes"),
+  ("°C", "DegreesCelsius"),
+  ("°C/min", "DegreesCelsiusPerMinute"),
+];
+
+const KEYWORDS: &[&str] = &[
+  "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for",
+  "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static",
+  "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Replaces German umlauts and `ß` with their ASCII transliteration.
+fn transliterate(s: &str) -> String {
+  s.replace('ä', "ae")
+    .replace('ö', "oe")
+    .replace('ü', "ue")
+    .replace('Ä', "Ae")
+    .replace('Ö', "Oe")
+    .replace('Ü', "Ue")
+    .replace('ß', "ss")
+}
+
+/// Converts a name to a `snake_case` identifier, e.g. `TiefpassTemperaturwert_ATS` to `tiefpass_temperaturwert_ats`.
+fn snake_case(s: &str) -> String {
+  // Split words at lowercase to uppercase boundaries.
+  let mut prev_lowercase = false;
+  let s: String = transliterate(s)
+    .chars()
+    .flat_map(|c| {
+      let split = prev_lowercase && c.is_ascii_uppercase();
+      prev_lowercase = c.is_ascii_lowercase();
+      split.then_some('_').into_iter().chain(c.to_lowercase())
+    })
+    .collect();
+
+  let mut ident =
+    s.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join("_");
+
+  if ident.starts_with(|c: char| c.is_ascii_digit()) || KEYWORDS.contains(&ident.as_str()) {
+    ident.insert(0, '_');
+  }
+
+  ident
+}
+
+/// Converts a name to a `CamelCase` identifier, e.g. `Betriebsart_A1M1` to `BetriebsartA1M1`.
+fn camel_case(s: &str) -> String {
+  let s = transliterate(s);
+
+  let mut ident = String::new();
+  for word in s.split(|c: char| !c.is_ascii_alphanumeric()) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+      ident.push(first.to_ascii_uppercase());
+      ident.extend(chars);
+    }
+  }
+
+  if ident.starts_with(|c: char| c.is_ascii_digit()) {
+    ident.insert(0, 'V');
+  }
+
+  ident
+}
+
+/// Converts a mapping label to a variant name, e.g. `A1/M1 + M2` to `A1M1PlusM2`.
+fn variant_name(label: &str) -> String {
+  let label = label
+    .replace('+', " plus ")
+    .replace('>', " gt ")
+    .replace('<', " lt ")
+    .replace('=', " eq ")
+    .replace(" - ", " minus ")
+    .replace('%', " percent ");
+  camel_case(&label.to_lowercase())
+}
+
+/// Returns the Rust type for the value of a command, or `None` if it is only available as a `Value`.
+fn value_type(command: &Command, typed_mappings: &BTreeMap<u16, String>) -> Option<String> {
+  let ty = match (&command.conversion, command.data_type) {
+    (
+      Some(
+        Conversion::HexByteToAsciiByte
+        | Conversion::HexByteToUtf16Byte
+        | Conversion::HexByteToDecimalByte
+        | Conversion::HexByteToVersion
+        | Conversion::FixedStringTerminalZeroes,
+      ),
+      _,
+    ) => "String".into(),
+    (Some(Conversion::DayMonthBcd), _) => "crate::types::DayMonth".into(),
+    (Some(Conversion::DayToDate), _) => "crate::types::Date".into(),
+    (Some(Conversion::Estrich), _) => "crate::types::ScreedDrying".into(),
+    (Some(Conversion::IpAddress), _) => "::std::net::Ipv4Addr".into(),
+    (_, DataType::Int | DataType::Byte | DataType::Double) => {
+      if let Some(mapping) = command.mapping.and_then(|mapping| typed_mappings.get(&mapping)) {
+        format!("mappings::{mapping}")
+      } else if let Some((_, unit)) = command.unit.as_deref().and_then(|unit| UNITS.iter().find(|(u, _)| *u == unit)) {
+        format!("units::{unit}")
+      } else if command.data_type == DataType::Double {
+        "f64".into()
+      } else {
+        "i64".into()
+      }
+    },
+    (_, DataType::String) => "String".into(),
+    (_, DataType::Date) => "crate::types::Date".into(),
+    (_, DataType::DateTime) => "crate::types::DateTime".into(),
+    (_, DataType::CircuitTimes) => "crate::types::CircuitTimes".into(),
+    (_, DataType::Error) => "crate::types::Error".into(),
+    (_, DataType::ErrorIndex | DataType::ByteArray) => "Vec<u8>".into(),
+    (_, DataType::DeviceId | DataType::DeviceIdF0) => return None,
+  };
+
+  Some(if command.block_count.is_some() { format!("Vec<{ty}>") } else { ty })
+}
+
+fn generate_typed() -> anyhow::Result<()> {
+  println!("Generating typed commands.");
+
+  let translations: BTreeMap<u16, Translation> = load_json("translations.used.json")?;
+  let mappings: BTreeMap<u16, BTreeMap<i32, u16>> = load_json("mappings.used.json")?;
+  let commands: BTreeMap<u16, Command> = load_json("event_types.used.json")?;
+  let devices: BTreeMap<String, Device> = load_json("devices.used.json")?;
+
+  let mut file = output_file("typed.rs")?;
+
+  writeln!(file, "/// Newtypes for values with a unit.\npub mod units {{")?;
+  for (unit, name) in UNITS {
+    writeln!(file, "  unit!({name}, {unit:?});")?;
+  }
+  writeln!(file, "}}")?;
+
+  // Only mappings of integer values are represented as enums. Each enum is named after the shortest name of the
+  // commands using it, e.g. `Betriebsart` for a mapping used by `Betriebsart` and `Betriebsart_M2`.
+  let mut mapping_commands = BTreeMap::<u16, &str>::new();
+  for command in commands.values() {
+    if let Some(mapping) = command.mapping
+      && matches!(command.data_type, DataType::Int | DataType::Byte)
+    {
+      let name = mapping_commands.entry(mapping).or_insert(&command.name);
+      if (command.name.len(), command.name.as_str()) < (name.len(), *name) {
+        *name = &command.name;
+      }
+    }
+  }
+
+  let mut typed_mappings = BTreeMap::new();
+  let mut mapping_names = BTreeSet::new();
+  for (mapping, command_name) in mapping_commands {
+    let mut name = camel_case(command_name);
+    if !mapping_names.insert(name.clone()) {
+      name = format!("{name}{mapping}");
+      mapping_names.insert(name.clone());
+    }
+    typed_mappings.insert(mapping, name);
+  }
+
+  writeln!(file, "\n/// Enums for values with a mapping.\npub mod mappings {{")?;
+  for (mapping, name) in &typed_mappings {
+    writeln!(file, "  mapping!({name}, MAPPING_{mapping}, {{")?;
+
+    let mut variants = Vec::<String>::new();
+    for (key, translation) in &mappings[mapping] {
+      let mut variant = variant_name(translations[translation].get(CULTURES[0]));
+      if variant.is_empty() || variants.contains(&variant) {
+        variant =
+          format!("{}{}", if variant.is_empty() { "V" } else { &variant }, key.to_string().replace('-', "Minus"));
+      }
+
+      writeln!(file, "    {variant} = {key},")?;
+      variants.push(variant);
+    }
+
+    writeln!(file, "  }});")?;
+  }
+  writeln!(file, "}}")?;
+
+  // Command names are unique, but different names can result in the same identifier, e.g. `K24_KonfiStatusWartung`
+  // and `K24_Konfi_Status_Wartung`, so disambiguate them using the command ID.
+  let mut type_names = BTreeMap::new();
+  let mut idents = BTreeSet::new();
+  let mut values = String::new();
+  let mut accessors = String::new();
+  for (command_id, command) in &commands {
+    let Some(ty) = value_type(command, &typed_mappings) else { continue };
+
+    let mut type_name = camel_case(&command.name);
+    if !idents.insert(type_name.clone()) {
+      type_name = format!("{type_name}{command_id}");
+      idents.insert(type_name.clone());
+    }
+    let mut method_name = snake_case(&command.name);
+    if !idents.insert(method_name.clone()) {
+      method_name = format!("{method_name}_{command_id}");
+      idents.insert(method_name.clone());
+    }
+    let name = &command.name;
+
+    values.push_str(&format!("  /// The value of `{name}`.\n  pub type {type_name} = {ty};\n"));
+
+    accessors.push_str(&format!("  command!({type_name}, {name:?}"));
+    if command.mode.is_read() {
+      accessors.push_str(&format!(", get = {method_name}"));
+    }
+    if command.mode.is_write() {
+      accessors.push_str(&format!(", set = set_{}", method_name.trim_start_matches('_')));
+    }
+    accessors.push_str(");\n");
+
+    type_names.insert(*command_id, type_name);
+  }
+
+  writeln!(file, "\n/// Value types of all commands.\npub mod values {{")?;
+  writeln!(file, "  #[allow(unused_imports)]\n  use super::{{mappings, units}};\n")?;
+  write!(file, "{values}")?;
+  writeln!(file, "}}")?;
+
+  writeln!(file, "\n/// Accessor traits of all commands.\npub mod commands {{")?;
+  write!(file, "{accessors}")?;
+  writeln!(file, "}}")?;
+
+  for (device_name, device) in &devices {
+    writeln!(file, "\npub mod {} {{", snake_case(device_name))?;
+    writeln!(file, "  device!({device_name:?}, {{")?;
+    for command_id in &device.commands {
+      if let Some(type_name) = type_names.get(command_id) {
+        writeln!(file, "    {type_name},")?;
+      }
+    }
+    writeln!(file, "  }});")?;
+    writeln!(file, "}}")?;
+  }
+
+  Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
   generate_translations()?;
   generate_mappings()?;
@@ -201,6 +507,10 @@ fn main() -> anyhow::Result<()> {
   generate_system_commands()?;
   generate_devices(&command_name_map)?;
 
+  if env::var_os("CARGO_FEATURE_TYPED").is_some() {
+    generate_typed()?;
+  }
+
   Ok(())
 }
 
@@ -221,6 +531,8 @@ pub struct Command {
   name: String,
   addr: u16,
   mode: AccessMode,
+  read_function: Option<String>,
+  write_function: Option<String>,
   data_type: DataType,
   parameter: Parameter,
   block_count: Option<usize>,
@@ -232,8 +544,14 @@ pub struct Command {
   conversion: Option<Conversion>,
   lower_border: Option<f64>,
   upper_border: Option<f64>,
+  stepping: Option<f64>,
   unit: Option<String>,
   mapping: Option<u16>,
+  default_value: Option<serde_json::Value>,
+  description: Option<u16>,
+  priority: Option<u8>,
+  filter_criterion: Option<bool>,
+  url: Option<String>,
 }
 
 impl fmt::Debug for Command {
@@ -244,6 +562,39 @@ impl fmt::Debug for Command {
       "None".into()
     };
 
+    // Default values are stored as text, byte arrays as space-separated hex bytes.
+    let default_value = match &self.default_value {
+      Some(serde_json::Value::Bool(b)) => Some((*b as u8).to_string()),
+      Some(serde_json::Value::Number(n)) => Some(n.to_string()),
+      Some(serde_json::Value::String(s)) => Some(s.clone()),
+      Some(serde_json::Value::Array(values)) => values
+        .iter()
+        .map(|v| v.as_u64().and_then(|n| u8::try_from(n).ok()).map(|b| format!("{b:02X}")))
+        .collect::<Option<Vec<_>>>()
+        .map(|bytes| bytes.join(" ")),
+      _ => None,
+    };
+    let default_value = match default_value {
+      Some(default_value) => format!("Some({default_value:?})"),
+      None => "None".into(),
+    };
+
+    let description = if let Some(description) = &self.description {
+      format!(
+        "Some(crate::Translation {{ {} }})",
+        CULTURES
+          .iter()
+          .map(|culture| format!("{}: crate::mappings::TRANSLATION_{}_{}", culture, description, culture.to_uppercase()))
+          .collect::<Vec<_>>()
+          .join(", ")
+      )
+    } else {
+      "None".into()
+    };
+
+    let read_function = camel_case(self.read_function.as_deref().unwrap_or("virtual_read"));
+    let write_function = camel_case(self.write_function.as_deref().unwrap_or("virtual_write"));
+
     let conversion = if let Some(conversion) = &self.conversion {
       format!("Some(crate::conversion::Conversion::{:?})", conversion)
     } else {
@@ -253,6 +604,8 @@ impl fmt::Debug for Command {
     f.debug_struct("crate::Command")
       .field("addr", &format_args!("0x{:04X}", self.addr))
       .field("mode", &format_args!("crate::AccessMode::{:?}", self.mode))
+      .field("read_function", &format_args!("crate::Function::{}", read_function))
+      .field("write_function", &format_args!("crate::Function::{}", write_function))
       .field("data_type", &format_args!("crate::DataType::{:?}", self.data_type))
       .field("parameter", &format_args!("crate::Parameter::{:?}", self.parameter))
       .field("block_count", &self.block_count)
@@ -264,8 +617,14 @@ impl fmt::Debug for Command {
       .field("conversion", &format_args!("{}", conversion))
       .field("lower_bound", &self.lower_border)
       .field("upper_bound", &self.upper_border)
+      .field("stepping", &self.stepping)
       .field("unit", &self.unit)
       .field("mapping", &format_args!("{}", mapping))
+      .field("default_value", &format_args!("{}", default_value))
+      .field("description", &format_args!("{}", description))
+      .field("priority", &self.priority)
+      .field("filter_criterion", &self.filter_criterion)
+      .field("url", &self.url)
       .finish()
   }
 }
@@ -97,7 +97,7 @@ fn main() -> anyhow::Result<()> {
     .arg("-SfL")
     .arg("--continue")
     .arg("-")
-    .arg(&url)
+    .arg(url)
     .arg("--output")
     .arg("vitosoft.exe")
     .spawn()?
@@ -14,7 +14,7 @@ use stringcase::snake_case;
 
 use codegen::{
   cleaned::{self, Conversion, ConversionInner},
-  command::Command,
+  command::{Command, read_function, write_function},
   files, raw,
   unique_mapping::UniqueMapping,
 };
@@ -218,8 +218,7 @@ fn clean_event_type_name(name: &str) -> &str {
   let name = name.strip_prefix("@@viessmann.eventtype.name.viessmann.eventtype.name.").unwrap_or(name);
   let name = name.strip_prefix("@@viessmann.eventtype.name.").unwrap_or(name);
   let name = name.strip_prefix("@@viessmann-ess.eventtype.name.viessmann.eventtype.name.").unwrap_or(name);
-  let name = name.strip_prefix("@@viessmann-ess.eventtype.name.").unwrap_or(name);
-  name
+  name.strip_prefix("@@viessmann-ess.eventtype.name.").unwrap_or(name)
 }
 
 fn is_data_point_type_supported(data_point_type_address: &str, data_point_type: &cleaned::DataPointType) -> bool {
@@ -278,8 +277,15 @@ fn is_event_type_supported(event_type_id: &str, event_type: &cleaned::EventType)
     return false;
   }
 
-  event_type.fc_read.as_deref().is_some_and(|fc| fc == "virtual_read")
-    || event_type.fc_write.as_deref().is_some_and(|fc| fc == "virtual_write")
+  // The access mode has to include a supported function.
+  let readable = event_type.fc_read.as_deref().and_then(read_function).is_some();
+  let writable = event_type.fc_write.as_deref().and_then(write_function).is_some();
+  match event_type.access_mode.as_str() {
+    "read" => readable,
+    "write" => writable,
+    "read_write" => readable || writable,
+    _ => false,
+  }
 }
 
 fn translations_raw() -> anyhow::Result<BTreeMap<String, BTreeMap<String, String>>> {
@@ -352,7 +358,7 @@ fn add_missing_enum_replace_value_translations(
 
     let enum_text = raw::clean_enum_text(Some(&enum_replace_value), None, event_value_type.description.clone());
 
-    if let Some(reverse_translation_id) = cleaned::parse_description(&enum_text, &translations, reverse_translations) {
+    if let Some(reverse_translation_id) = cleaned::parse_description(&enum_text, translations, reverse_translations) {
       event_value_type.enum_replace_value = reverse_translation_id;
       return;
     }
@@ -387,7 +393,7 @@ fn main() -> anyhow::Result<()> {
 
   let versions: BTreeMap<_, _> =
     ecn_data_set.ecn_version.into_iter().map(|version| (snake_case(&version.name), version.value)).collect();
-  save_json(format!("versions.used.json"), &versions)?;
+  save_json("versions.used.json", &versions)?;
 
   let data_point_types_raw: BTreeMap<_, _> = ecn_data_set
     .ecn_datapoint_type
@@ -466,7 +472,6 @@ fn main() -> anyhow::Result<()> {
             table_extension
               .field_name
               .strip_prefix(&format!("label.tableextension.{}.", table_extension.table_name))
-              .map(|s| s)
               .unwrap_or(&table_extension.field_name),
           ),
           internal_data_type: table_extension.internal_data_type,
@@ -477,9 +482,7 @@ fn main() -> anyhow::Result<()> {
               "" => None,
               s => Some(serde_json::Value::Number(s.parse::<u8>().unwrap().into())),
             },
-            Ok(serde_json::Value::String(s)) => match s.as_str() {
-              _ => Some(serde_json::Value::String(s)),
-            },
+            Ok(serde_json::Value::String(s)) => Some(serde_json::Value::String(s)),
             v => unreachable!("unhandled default value: {v:?}"),
           },
           options_value: table_extension
@@ -490,7 +493,7 @@ fn main() -> anyhow::Result<()> {
               (v.to_owned(), k.to_owned())
             })
             .collect(),
-          pk_fields: table_extension.pk_fields.iter().map(|field| snake_case(&field)).collect(),
+          pk_fields: table_extension.pk_fields.iter().map(|field| snake_case(field)).collect(),
           table_name: table_extension.table_name,
         },
       )
@@ -526,7 +529,7 @@ fn main() -> anyhow::Result<()> {
 
   let event_value_types_cleaned: BTreeMap<_, _> = event_value_types_raw
     .into_iter()
-    .filter_map(|(event_value_type_id, mut event_value_type)| {
+    .map(|(event_value_type_id, mut event_value_type)| {
       let unit = cleaned::parse_unit(&event_value_type.unit);
 
       let data_type = match event_value_type.data_type.as_str() {
@@ -611,7 +614,7 @@ fn main() -> anyhow::Result<()> {
         _ => unreachable!(),
       };
 
-      Some((event_value_type_id, data_type))
+      (event_value_type_id, data_type)
     })
     .collect();
 
@@ -784,8 +787,7 @@ fn main() -> anyhow::Result<()> {
           event_type_cleaned.unit = unit;
         },
         Some(cleaned::EventValueType::Multiple { value_list }) => {
-          event_type_cleaned.value_list =
-            event_type_cleaned.value_list.into_iter().chain(value_list.into_iter()).collect();
+          event_type_cleaned.value_list = event_type_cleaned.value_list.into_iter().chain(value_list).collect();
         },
         None => (),
       }
@@ -794,7 +796,7 @@ fn main() -> anyhow::Result<()> {
     })
     .collect();
 
-  for (_, table_extension_value) in &table_extension_values_raw {
+  for table_extension_value in table_extension_values_raw.values() {
     let table_extension = table_extensions_raw.get(&table_extension_value.ref_id).unwrap();
 
     let pk: BTreeMap<_, _> = table_extension.pk_fields.iter().zip(table_extension_value.pk_value.iter()).collect();
@@ -976,22 +978,21 @@ fn main() -> anyhow::Result<()> {
     .collect();
 
   let data_point_types_cleaned: BTreeMap<_, _> = data_point_types_cleaned
-    .into_iter()
-    .filter_map(|(_, mut data_point_type_cleaned)| {
+    .into_values()
+    .filter_map(|mut data_point_type_cleaned| {
       let data_point_type_cleaned_address = data_point_type_cleaned.address.take().unwrap();
 
       if !is_data_point_type_supported(&data_point_type_cleaned_address, &data_point_type_cleaned) {
         return None;
       }
 
-      data_point_type_cleaned.event_types =
-        data_point_type_cleaned.event_types.into_iter().filter(|id| event_types_cleaned.contains_key(id)).collect();
+      data_point_type_cleaned.event_types.retain(|id| event_types_cleaned.contains_key(id));
 
       Some((data_point_type_cleaned_address, data_point_type_cleaned))
     })
     .collect();
 
-  let event_type_ids: BTreeSet<_> = event_types_cleaned.iter().map(|(_, event_type)| &event_type.type_id).collect();
+  let event_type_ids: BTreeSet<_> = event_types_cleaned.values().map(|event_type| &event_type.type_id).collect();
 
   let system_event_types_cleaned: BTreeMap<_, _> = system_event_types
     .into_iter()
@@ -1068,7 +1069,7 @@ fn main() -> anyhow::Result<()> {
         .event_types
         .iter()
         .filter(|event_type_id| {
-          let type_id = event_types_cleaned.get(&event_type_id).as_ref().unwrap().type_id.as_str();
+          let type_id = event_types_cleaned.get(event_type_id).as_ref().unwrap().type_id.as_str();
           !matches!(type_id, "ecnsysEventType~Error" | "ecnsysEventType~ErrorIndex")
         })
         .count()
@@ -1110,14 +1111,14 @@ fn main() -> anyhow::Result<()> {
       event_type.type_id.clone(),
       value_list
         .iter()
-        .map(|(value, translation_id)| (value.clone(), translations_unique.mapping.get(translation_id).unwrap()))
+        .map(|(value, translation_id)| (*value, translations_unique.mapping.get(translation_id).unwrap()))
         .collect(),
     );
 
     acc
   });
 
-  let mappings_unique = UniqueMapping::create(error_mappings.into_iter().chain(mappings.into_iter()).collect());
+  let mappings_unique = UniqueMapping::create(error_mappings.into_iter().chain(mappings).collect());
   save_json("mappings.unique.json", &mappings_unique)?;
 
   let devices_used: BTreeMap<_, _> = {
@@ -1164,11 +1165,11 @@ fn main() -> anyhow::Result<()> {
       acc
     });
 
-    event_types_cleaned.into_iter().filter(|(event_type_id, _)| used_event_type_ids.contains(&event_type_id)).fold(
+    event_types_cleaned.into_iter().filter(|(event_type_id, _)| used_event_type_ids.contains(event_type_id)).fold(
       BTreeMap::new(),
       |mut acc, (id, event_type)| {
         let event_type_id = event_type.type_id.clone();
-        acc.insert(id, Command::from_event_type(event_type_id, event_type, &mappings_unique));
+        acc.insert(id, Command::from_event_type(event_type_id, event_type, &mappings_unique, &translations_unique));
         acc
       },
     )
@@ -1196,11 +1197,17 @@ fn main() -> anyhow::Result<()> {
         .collect::<Vec<_>>()
         .join("_");
 
-      (id, Command::from_event_type(event_type_id, event_type, &mappings_unique))
+      (id, Command::from_event_type(event_type_id, event_type, &mappings_unique, &translations_unique))
     })
     .collect();
   save_json("system_event_types.used.json", &system_event_types_used)?;
 
+  let descriptions_used: BTreeSet<_> = system_event_types_used
+    .values()
+    .chain(event_types_used.values())
+    .filter_map(|command| command.description)
+    .collect();
+
   let mappings_used: BTreeMap<_, _> = {
     let device_error_mappings = devices_used.into_values().map(|v| v.error_mapping);
     let command_mappings = event_types_used.into_values().filter_map(|v| v.mapping);
@@ -1209,10 +1216,28 @@ fn main() -> anyhow::Result<()> {
   };
   save_json("mappings.used.json", &mappings_used)?;
 
+  // Unique translations are deduplicated using their English text, so use the German text of any translation
+  // with the same ID, falling back to the English text if there is none.
+  let translations_de = {
+    let translations: BTreeMap<_, _> = translations.iter().map(|(k, v)| (translation_fixes(k), v)).collect();
+
+    translations_unique.mapping.iter().fold(BTreeMap::new(), |mut acc, (k, translation_id)| {
+      if let Some(text) = translations.get(k) {
+        acc.entry(*translation_id).or_insert(*text);
+      }
+      acc
+    })
+  };
+
   let translations_used: BTreeMap<_, _> = mappings_used
     .into_values()
-    .flat_map(|translations| translations.values())
-    .map(|translation_id| (translation_id, translations_unique.translations.get(translation_id).unwrap()))
+    .flat_map(|translations| translations.values().map(|translation_id| **translation_id))
+    .chain(descriptions_used)
+    .map(|translation_id| {
+      let en = translations_unique.translations.get(&translation_id).unwrap();
+      let de = translations_de.get(&translation_id).copied().unwrap_or(en);
+      (translation_id, BTreeMap::from([("de", de), ("en", en)]))
+    })
     .collect();
   save_json("translations.used.json", &translations_used)?;
 
@@ -8,6 +8,63 @@ use crate::{
   unique_mapping::UniqueMapping,
 };
 
+/// Read functions supported by both the VS1 and VS2 protocol.
+pub const READ_FUNCTIONS: &[&str] = &["virtual_read", "gfa_read", "process_read"];
+
+/// Write functions supported by both the VS1 and VS2 protocol.
+pub const WRITE_FUNCTIONS: &[&str] = &["virtual_write", "gfa_write", "process_write"];
+
+/// Returns the supported function for the given read function, if any.
+///
+/// GWG functions, e.g. `gwg_virtual_read`, are mapped to their generic counterpart, e.g. `virtual_read`,
+/// a plain `gwg_read` reads the physical memory.
+pub fn read_function(fc: &str) -> Option<&str> {
+  match fc.strip_prefix("gwg_") {
+    Some("read" | "physical_read") => Some("physical_read"),
+    Some("virtual_read") => Some("virtual_read"),
+    Some(_) => None,
+    None => READ_FUNCTIONS.contains(&fc).then_some(fc),
+  }
+}
+
+/// Returns the supported function for the given write function, if any.
+///
+/// GWG functions are mapped like in `read_function`.
+pub fn write_function(fc: &str) -> Option<&str> {
+  match fc.strip_prefix("gwg_") {
+    Some("write" | "physical_write") => Some("physical_write"),
+    Some("virtual_write") => Some("virtual_write"),
+    Some(_) => None,
+    None => WRITE_FUNCTIONS.contains(&fc).then_some(fc),
+  }
+}
+
+/// Returns the access mode restricted to the supported functions, and the read and write functions to store.
+///
+/// Non-virtual functions are stored, e.g. `gfa_read` or `process_write`. Unsupported functions are stored as is,
+/// but reading or writing is removed from the access mode.
+fn access(
+  event_type_id: &str,
+  access_mode: &str,
+  fc_read: Option<&str>,
+  fc_write: Option<&str>,
+) -> (&'static str, Option<String>, Option<String>) {
+  let readable = fc_read.and_then(read_function).is_some();
+  let writable = fc_write.and_then(write_function).is_some();
+  let read_function =
+    fc_read.map(|fc| read_function(fc).unwrap_or(fc)).filter(|&fc| fc != "virtual_read").map(str::to_owned);
+  let write_function =
+    fc_write.map(|fc| write_function(fc).unwrap_or(fc)).filter(|&fc| fc != "virtual_write").map(str::to_owned);
+  let mode = match (access_mode, readable, writable) {
+    ("read_write", true, true) => "read_write",
+    ("read" | "read_write", true, _) => "read",
+    ("write" | "read_write", _, true) => "write",
+    (mode, ..) => panic!("unsupported access mode {mode} for event type {event_type_id}"),
+  };
+
+  (mode, read_function, write_function)
+}
+
 #[derive(Debug, Serialize)]
 pub struct Command {
   pub addr: u16,
@@ -27,6 +84,12 @@ pub struct Command {
   pub conversion: Option<Conversion>,
   pub data_type: String,
   #[serde(skip_serializing_if = "Option::is_none")]
+  pub default_value: Option<serde_json::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub filter_criterion: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub lower_border: Option<f64>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub mapping: Option<usize>,
@@ -36,9 +99,19 @@ pub struct Command {
   pub name: String,
   pub parameter: String,
   #[serde(skip_serializing_if = "Option::is_none")]
+  pub priority: Option<u8>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub read_function: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stepping: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub unit: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub upper_border: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub write_function: Option<String>,
 }
 
 impl Command {
@@ -46,6 +119,7 @@ impl Command {
     event_type_id: String,
     event_type: cleaned::EventType,
     mappings_unique: &UniqueMapping<String, BTreeMap<i32, &usize>>,
+    translations_unique: &UniqueMapping<String, String>,
   ) -> Command {
     let mut data_type = event_type.value_type.or(event_type.sdk_data_type).unwrap();
 
@@ -76,6 +150,16 @@ impl Command {
     let enum_type = !event_type.value_list.is_empty();
     let mapping = if enum_type { mappings_unique.mapping.get(&event_type_id).cloned() } else { None };
 
+    // Descriptions are references to translations, e.g. `@@viessmann.eventtype.name.description`.
+    let description = event_type
+      .description
+      .as_deref()
+      .and_then(|description| description.strip_prefix("@@"))
+      .and_then(|translation_id| translations_unique.mapping.get(translation_id).cloned());
+
+    let (mode, read_function, write_function) =
+      access(&event_type_id, &event_type.access_mode, event_type.fc_read.as_deref(), event_type.fc_write.as_deref());
+
     Command {
       addr: u16::from_str_radix(event_type.address.as_deref().unwrap().strip_prefix("0x").unwrap(), 16).unwrap(),
       bit_len: event_type.bit_length,
@@ -86,14 +170,70 @@ impl Command {
       byte_pos: event_type.byte_position,
       conversion,
       data_type,
+      default_value: event_type.default_value,
+      description,
+      filter_criterion: event_type.filter_criterion,
       lower_border: event_type.lower_border,
       mapping,
       mapping_type: event_type.mapping_type,
-      mode: event_type.access_mode.to_owned(),
+      mode: mode.to_owned(),
       name: event_type_id.to_owned(),
       parameter: snake_case(event_type.parameter.as_deref().unwrap()),
+      priority: event_type.priority,
+      read_function,
+      stepping: event_type.stepping.filter(|stepping| *stepping > 0.0),
       unit: event_type.unit,
       upper_border: event_type.upper_border,
+      url: event_type.url,
+      write_function,
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn gwg_functions() {
+    assert_eq!(read_function("gwg_virtual_read"), Some("virtual_read"));
+    assert_eq!(read_function("gwg_read"), Some("physical_read"));
+    assert_eq!(read_function("gwg_physical_read"), Some("physical_read"));
+    assert_eq!(read_function("gwg_eeprom_read"), None);
+    assert_eq!(write_function("gwg_virtual_write"), Some("virtual_write"));
+    assert_eq!(write_function("gwg_write"), Some("physical_write"));
+    assert_eq!(write_function("gwg_physical_write"), Some("physical_write"));
+
+    assert_eq!(
+      access("A", "read_write", Some("gwg_read"), Some("gwg_virtual_write")),
+      ("read_write", Some("physical_read".into()), None)
+    );
+  }
+
+  #[test]
+  fn access_virtual_functions() {
+    assert_eq!(access("A", "read_write", Some("virtual_read"), Some("virtual_write")), ("read_write", None, None));
+    assert_eq!(
+      access("A", "read_write", Some("gfa_read"), Some("process_write")),
+      ("read_write", Some("gfa_read".into()), Some("process_write".into()))
+    );
+  }
+
+  #[test]
+  fn access_unsupported_functions() {
+    assert_eq!(
+      access("A", "read_write", Some("virtual_read"), Some("eeprom_write")),
+      ("read", None, Some("eeprom_write".into()))
+    );
+    assert_eq!(
+      access("A", "read_write", Some("kbus_virtual_read"), Some("virtual_write")),
+      ("write", Some("kbus_virtual_read".into()), None)
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "unsupported access mode read for event type A")]
+  fn access_without_supported_functions() {
+    access("A", "read", Some("eeprom_read"), None);
+  }
+}
@@ -17,15 +17,20 @@ pub async fn cat(optolink: Optolink) -> Result<(), Box<dyn std::error::Error>> {
     commands.insert(command_name, command);
   }
 
-  for (command_name, command) in commands {
-    let readable = command.access_mode().is_read();
-    if !readable {
-      log::warn!("Command '{command_name}' is not readable.");
-      continue;
-    }
-
-    let res = vcontrol.get(command_name).await;
-
+  let command_names = commands
+    .into_iter()
+    .filter_map(|(command_name, command)| {
+      let readable = command.access_mode().is_read();
+      if !readable {
+        log::warn!("Command '{command_name}' is not readable.");
+      }
+      readable.then_some(*command_name)
+    })
+    .collect::<Vec<_>>();
+
+  let values = vcontrol.get_many(&command_names).await?;
+
+  for (command_name, res) in command_names.into_iter().zip(values) {
     match res {
       Ok(value) => {
         println!("{}:", command_name);
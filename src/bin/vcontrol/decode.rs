@@ -0,0 +1,56 @@
+use std::io::Read;
+
+use clap::{Arg, ArgMatches, Command as App, builder::PossibleValuesParser};
+
+use vcontrol::{
+  Device,
+  decode::{self, Decoder},
+};
+
+pub fn app() -> App {
+  App::new("decode")
+    .about("decode a captured byte stream, e.g. `12:00:00.123 41 05 00 01 00 F8 08 06` per line")
+    .arg(Arg::new("file").help("path of the capture, or `-` for standard input").required(true))
+    .arg(
+      Arg::new("format")
+        .long("format")
+        .value_parser(PossibleValuesParser::new(["auto", "hex", "binary"]))
+        .default_value("auto")
+        .help("format of the capture"),
+    )
+    .arg(
+      Arg::new("device-name")
+        .long("device-name")
+        .help("name of the device, e.g. `VScotHO1_4` (default: detected from the capture)"),
+    )
+}
+
+pub fn decode(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+  let file = matches.get_one::<String>("file").unwrap();
+
+  let bytes = if file == "-" {
+    let mut bytes = Vec::new();
+    std::io::stdin().read_to_end(&mut bytes)?;
+    bytes
+  } else {
+    std::fs::read(file)?
+  };
+
+  let chunks = match matches.get_one::<String>("format").map(String::as_str) {
+    Some("hex") => decode::parse_hex(std::str::from_utf8(&bytes)?)?,
+    Some("binary") => decode::parse_binary(&bytes),
+    _ => decode::parse(&bytes)?,
+  };
+
+  let mut decoder = Decoder::new();
+  if let Some(name) = matches.get_one::<String>("device-name") {
+    let device = Device::by_name(name).ok_or_else(|| format!("Unknown device “{name}”."))?;
+    decoder = decoder.device(device);
+  }
+
+  for frame in decoder.decode(&chunks) {
+    println!("{frame}");
+  }
+
+  Ok(())
+}
@@ -1,12 +1,28 @@
-use std::process::exit;
+use std::{io, process::exit};
 
 use clap::{Arg, ArgAction, Command, crate_version};
-use serde_json;
 
-use vcontrol::{Optolink, VControl, Value};
+use vcontrol::{Optolink, VControl, Value, record::Replay};
 
 mod cat;
+mod decode;
+#[cfg(feature = "mqtt")]
+mod mqtt;
 mod scan;
+mod schedule;
+#[cfg(feature = "http")]
+mod serve;
+mod vcontrold;
+
+/// Spawns a handle connected to the given simulator.
+#[cfg(all(test, feature = "sim"))]
+async fn simulated(simulator: vcontrol::sim::Simulator) -> vcontrol::VControlHandle {
+  let (stream, server) = tokio::io::duplex(256);
+  tokio::spawn(async move { simulator.serve(server).await });
+
+  let vcontrol = VControl::connect(Optolink::from_stream(stream)).await.unwrap();
+  vcontrol::VControlHandle::spawn(vcontrol)
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -21,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .short('d')
         .long("device")
         .action(ArgAction::Set)
-        .conflicts_with_all(&["host", "port"])
+        .conflicts_with_all(["host", "port"])
         .help("path of the device"),
     )
     .arg(
@@ -41,6 +57,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .conflicts_with("device")
         .help("port of the device"),
     )
+    .arg(
+      Arg::new("record")
+        .long("record")
+        .action(ArgAction::Set)
+        .help("path of a file to record all bytes sent and received to"),
+    )
+    .arg(
+      Arg::new("replay")
+        .long("replay")
+        .action(ArgAction::Set)
+        .conflicts_with_all(["device", "host", "port", "record"])
+        .help("path of a recording to play back instead of connecting to a device"),
+    )
     .subcommand(
       Command::new("get").about("get value").arg(Arg::new("command").help("name of the command").required(true)),
     )
@@ -51,11 +80,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .arg(Arg::new("value").help("value").required(true)),
     )
     .subcommand(Command::new("cat").about("get all values"))
-    .subcommand(Command::new("scan").about("scan all values"));
+    .subcommand(Command::new("scan").about("scan all values"))
+    .subcommand(decode::app())
+    .subcommand(schedule::app())
+    .subcommand(vcontrold::app());
+
+  #[cfg(feature = "mqtt")]
+  let app = app.subcommand(mqtt::app());
+  #[cfg(feature = "http")]
+  let app = app.subcommand(serve::app());
 
   let matches = app.get_matches();
 
-  let optolink = if let Some(device) = matches.get_one::<String>("device") {
+  // Decoding a capture does not need a connection.
+  if let Some(matches) = matches.subcommand_matches("decode") {
+    return decode::decode(matches);
+  }
+
+  let optolink = if let Some(path) = matches.get_one::<String>("replay") {
+    std::fs::read_to_string(path)
+      .and_then(|recording| {
+        recording.parse().map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {err}")))
+      })
+      .map(|recording| Optolink::from_transport(Replay::new(recording)))
+  } else if let Some(device) = matches.get_one::<String>("device") {
     Optolink::open(device).await
   } else if let Some(port) = matches.get_one::<String>("port") {
     let host = matches.get_one::<String>("host").map_or("localhost", |host| host);
@@ -73,6 +121,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     exit(1);
   });
 
+  let optolink = match matches.get_one::<String>("record") {
+    Some(path) => optolink.record(std::fs::File::create(path).unwrap_or_else(|err| {
+      eprintln!("Error: {}: {}", path, err);
+      exit(1);
+    })),
+    None => optolink,
+  };
+
   if let Some(matches) = matches.subcommand_matches("get") {
     let mut vcontrol = VControl::connect(optolink).await.unwrap_or_else(|err| {
       eprintln!("Error: {}", err);
@@ -120,11 +176,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     return Ok(());
   }
 
-  if let Some(_) = matches.subcommand_matches("cat") {
+  if matches.subcommand_matches("cat").is_some() {
     return cat::cat(optolink).await;
   }
 
-  if let Some(_) = matches.subcommand_matches("scan") {
+  #[cfg(feature = "mqtt")]
+  if let Some(matches) = matches.subcommand_matches("mqtt") {
+    return mqtt::mqtt(optolink, matches).await;
+  }
+
+  #[cfg(feature = "http")]
+  if let Some(matches) = matches.subcommand_matches("serve") {
+    return serve::serve(optolink, matches).await;
+  }
+
+  if let Some(matches) = matches.subcommand_matches("schedule") {
+    return schedule::schedule(optolink, matches).await;
+  }
+
+  if let Some(matches) = matches.subcommand_matches("vcontrold") {
+    return vcontrold::vcontrold(optolink, matches).await;
+  }
+
+  if matches.subcommand_matches("scan").is_some() {
     return scan::scan(optolink).await;
   }
 
@@ -0,0 +1,354 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  time::Duration,
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command as App, value_parser};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde_json::json;
+
+use vcontrol::{Command, DataType, Locale, Optolink, OutputValue, Priority, VControl, VControlHandle, Value};
+
+pub fn app() -> App {
+  App::new("mqtt")
+    .about("publish all values to an MQTT broker")
+    .arg(Arg::new("broker").long("broker").action(ArgAction::Set).default_value("localhost").help("MQTT broker host"))
+    .arg(
+      Arg::new("broker-port")
+        .long("broker-port")
+        .action(ArgAction::Set)
+        .value_parser(value_parser!(u16))
+        .default_value("1883")
+        .help("MQTT broker port"),
+    )
+    .arg(Arg::new("username").long("username").action(ArgAction::Set).requires("password").help("MQTT username"))
+    .arg(Arg::new("password").long("password").action(ArgAction::Set).requires("username").help("MQTT password"))
+    .arg(Arg::new("prefix").long("prefix").action(ArgAction::Set).default_value("vcontrol").help("MQTT topic prefix"))
+    .arg(
+      Arg::new("discovery-prefix")
+        .long("discovery-prefix")
+        .action(ArgAction::Set)
+        .default_value("homeassistant")
+        .help("Home Assistant discovery topic prefix"),
+    )
+    .arg(
+      Arg::new("interval")
+        .long("interval")
+        .action(ArgAction::Set)
+        .value_parser(value_parser!(u64))
+        .default_value("60")
+        .help("polling interval in seconds"),
+    )
+}
+
+/// Returns the MQTT payload for a value.
+fn state(value: &OutputValue) -> String {
+  match (&value.value, value.mapping) {
+    (Value::Int(n), Some(mapping)) => match mapping.get(*n as i32, Locale::default()) {
+      Some(label) => label.to_owned(),
+      None => n.to_string(),
+    },
+    (Value::Int(n), None) => n.to_string(),
+    (Value::Double(n), _) => n.to_string(),
+    (Value::String(s), _) => s.clone(),
+    (Value::Empty, _) => String::new(),
+    (value, _) => serde_json::to_string(value).unwrap(),
+  }
+}
+
+/// Parses an MQTT payload into a value, mapping labels are looked up when setting the value.
+fn parse_input(payload: &str) -> Value {
+  serde_json::from_str(payload).unwrap_or_else(|_| Value::String(payload.to_owned()))
+}
+
+/// Returns the Home Assistant discovery topic and config for a command.
+fn discovery(
+  discovery_prefix: &str,
+  prefix: &str,
+  device: &vcontrol::Device,
+  name: &str,
+  command: &Command,
+) -> (String, serde_json::Value) {
+  let readable = command.access_mode().is_read();
+  let writable = command.access_mode().is_write();
+  let numeric = matches!(command.data_type(), DataType::Byte | DataType::Int | DataType::Double);
+
+  let component = match (writable, command.mapping().is_some(), numeric) {
+    (true, true, _) => "select",
+    (true, false, true) => "number",
+    // A sensor cannot be written, so write-only values use a text entity.
+    (true, false, false) if !readable => "text",
+    _ => "sensor",
+  };
+
+  let object_id = format!("{}_{}", device.name(), name);
+  let mut config = json!({
+    "name": name,
+    "unique_id": format!("vcontrol_{object_id}"),
+    "object_id": object_id,
+    "device": {
+      "identifiers": [format!("vcontrol_{}", device.name())],
+      "name": device.name(),
+      "manufacturer": "Viessmann",
+    },
+  });
+
+  if readable {
+    config["state_topic"] = json!(format!("{prefix}/{name}"));
+  }
+
+  if writable {
+    config["command_topic"] = json!(format!("{prefix}/{name}/set"));
+  }
+
+  if let Some(mapping) = command.mapping() {
+    let mut options = mapping.labels(Locale::default()).entries().collect::<Vec<_>>();
+    options.sort_by_key(|(key, _)| **key);
+    let options = options.into_iter().map(|(_, label)| *label).collect::<Vec<_>>();
+
+    config["options"] = json!(options);
+    if component == "sensor" {
+      config["device_class"] = json!("enum");
+    }
+  }
+
+  if let Some(unit) = command.unit() {
+    config["unit_of_measurement"] = json!(unit);
+  }
+
+  if component == "number" {
+    if let Some(lower_bound) = command.lower_bound() {
+      config["min"] = json!(lower_bound);
+    }
+
+    if let Some(upper_bound) = command.upper_bound() {
+      config["max"] = json!(upper_bound);
+    }
+
+    if let Some(stepping) = command.stepping() {
+      config["step"] = json!(stepping);
+    }
+
+    config["mode"] = json!("box");
+  }
+
+  (format!("{discovery_prefix}/{component}/vcontrol/{}/config", config["object_id"].as_str().unwrap()), config)
+}
+
+/// Returns all system and device commands.
+fn commands(device: &vcontrol::Device) -> BTreeMap<&'static str, &'static Command> {
+  let mut commands = BTreeMap::new();
+
+  for (name, command) in vcontrol::commands::system_commands() {
+    commands.insert(*name, *command);
+  }
+
+  for (name, command) in device.commands() {
+    commands.insert(*name, *command);
+  }
+
+  commands
+}
+
+/// Returns the names of all commands which are polled.
+fn readable(commands: &BTreeMap<&'static str, &'static Command>) -> Vec<&'static str> {
+  commands.iter().filter(|(_, command)| command.access_mode().is_read()).map(|(name, _)| *name).collect()
+}
+
+/// Returns the topics which are subscribed to for setting values.
+fn set_topics(prefix: &str, commands: &BTreeMap<&'static str, &'static Command>) -> Vec<String> {
+  commands
+    .iter()
+    .filter(|(_, command)| command.access_mode().is_write())
+    .map(|(name, _)| format!("{prefix}/{name}/set"))
+    .collect()
+}
+
+pub async fn mqtt(optolink: Optolink, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+  let mut vcontrol = VControl::connect(optolink).await?;
+
+  log::info!("Connected to '{}' via {} protocol.", vcontrol.device().name(), vcontrol.protocol());
+
+  let handle = VControlHandle::spawn(vcontrol);
+  let device = handle.device();
+
+  let prefix = matches.get_one::<String>("prefix").unwrap().trim_end_matches('/').to_owned();
+  let discovery_prefix = matches.get_one::<String>("discovery-prefix").unwrap().trim_end_matches('/');
+  let interval = Duration::from_secs(*matches.get_one::<u64>("interval").unwrap());
+
+  let mut options = MqttOptions::new(
+    format!("vcontrol-{}", device.name()),
+    matches.get_one::<String>("broker").unwrap(),
+    *matches.get_one::<u16>("broker-port").unwrap(),
+  );
+  options.set_keep_alive(Duration::from_secs(30));
+  options.set_last_will(rumqttc::LastWill::new(format!("{prefix}/status"), "offline", QoS::AtLeastOnce, true));
+  if let (Some(username), Some(password)) =
+    (matches.get_one::<String>("username"), matches.get_one::<String>("password"))
+  {
+    options.set_credentials(username, password);
+  }
+
+  let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+  let commands = commands(device);
+
+  let discovery_configs = commands
+    .iter()
+    .map(|(name, command)| discovery(discovery_prefix, &prefix, device, name, command))
+    .collect::<Vec<_>>();
+  let set_topics = set_topics(&prefix, &commands);
+
+  let poll_handle = handle.clone().read_priority(Priority::Low);
+  let poll_client = client.clone();
+  let poll_prefix = prefix.clone();
+  tokio::spawn(async move {
+    let names = readable(&commands);
+    let mut last_states = HashMap::new();
+
+    loop {
+      match poll_handle.get_many(&names).await {
+        Ok(values) => {
+          for (name, value) in names.iter().zip(values) {
+            let state = match value {
+              Ok(value) => state(&value),
+              Err(err) => {
+                log::warn!("Failed to read '{name}': {err}");
+                continue;
+              },
+            };
+
+            if last_states.get(name) == Some(&state) {
+              continue;
+            }
+
+            if let Err(err) =
+              poll_client.publish(format!("{poll_prefix}/{name}"), QoS::AtLeastOnce, true, state.clone()).await
+            {
+              log::error!("Failed to publish '{name}': {err}");
+            }
+            last_states.insert(*name, state);
+          }
+        },
+        Err(err) => log::error!("Polling failed: {err}"),
+      }
+
+      tokio::time::sleep(interval).await;
+    }
+  });
+
+  loop {
+    let publish = match eventloop.poll().await {
+      Ok(Event::Incoming(Packet::Publish(publish))) => publish,
+      Ok(Event::Incoming(Packet::ConnAck(_))) => {
+        log::info!("Connected to MQTT broker.");
+
+        // Subscriptions are lost when reconnecting with a clean session, so (re-)announce everything.
+        // This is done in a separate task since requests are only sent while the event loop is polled.
+        let client = client.clone();
+        let status_topic = format!("{prefix}/status");
+        let discovery_configs = discovery_configs.clone();
+        let set_topics = set_topics.clone();
+        tokio::spawn(async move {
+          let res = async {
+            client.publish(status_topic, QoS::AtLeastOnce, true, "online").await?;
+
+            for (topic, config) in discovery_configs {
+              client.publish(topic, QoS::AtLeastOnce, true, config.to_string()).await?;
+            }
+
+            for topic in set_topics {
+              client.subscribe(topic, QoS::AtLeastOnce).await?;
+            }
+
+            Ok::<_, rumqttc::ClientError>(())
+          };
+
+          if let Err(err) = res.await {
+            log::error!("Failed to announce commands: {err}");
+          }
+        });
+
+        continue;
+      },
+      Ok(_) => continue,
+      Err(err) => {
+        log::error!("MQTT connection error: {err}");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        continue;
+      },
+    };
+
+    let Some(name) = publish.topic.strip_prefix(&format!("{prefix}/")).and_then(|topic| topic.strip_suffix("/set"))
+    else {
+      continue;
+    };
+
+    let Some(command) = device.command(name).or_else(|| vcontrol::commands::system_command(name)) else {
+      log::warn!("Received value for unknown command '{name}'.");
+      continue;
+    };
+    let readable = command.access_mode().is_read();
+
+    let payload = String::from_utf8_lossy(&publish.payload).into_owned();
+    let input = parse_input(&payload);
+
+    let handle = handle.clone();
+    let client = client.clone();
+    let topic = format!("{prefix}/{name}");
+    let name = name.to_owned();
+    tokio::spawn(async move {
+      if let Err(err) = handle.set(&name, input).await {
+        log::error!("Failed to set '{name}' to '{payload}': {err}");
+        return;
+      }
+
+      if !readable {
+        return;
+      }
+
+      match handle.get(&name).await {
+        Ok(value) => {
+          if let Err(err) = client.publish(topic, QoS::AtLeastOnce, true, state(&value)).await {
+            log::error!("Failed to publish '{name}': {err}");
+          }
+        },
+        Err(err) => log::warn!("Failed to read '{name}': {err}"),
+      }
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn topics_and_discovery() {
+    let device = vcontrol::Device::by_name("Ecotronic").unwrap();
+    let commands = commands(device);
+
+    let (&write_only, _) = commands
+      .iter()
+      .find(|(_, command)| !command.access_mode().is_read() && command.access_mode().is_write())
+      .unwrap();
+
+    let readable = readable(&commands);
+    assert!(!readable.contains(&write_only));
+    assert!(readable.contains(&"device_id"));
+
+    let set_topics = set_topics("vcontrol", &commands);
+    assert!(set_topics.contains(&format!("vcontrol/{write_only}/set")));
+    assert!(!set_topics.contains(&"vcontrol/device_id/set".to_owned()));
+
+    let (topic, config) = discovery("homeassistant", "vcontrol", device, write_only, commands[write_only]);
+    assert!(topic.ends_with(&format!("/vcontrol/Ecotronic_{write_only}/config")), "{topic}");
+    assert_eq!(config["command_topic"], format!("vcontrol/{write_only}/set"));
+    assert!(config.get("state_topic").is_none());
+
+    let (topic, config) = discovery("homeassistant", "vcontrol", device, "device_id", commands["device_id"]);
+    assert_eq!(topic, "homeassistant/sensor/vcontrol/Ecotronic_device_id/config");
+    assert_eq!(config["state_topic"], "vcontrol/device_id");
+    assert!(config.get("command_topic").is_none());
+  }
+}
@@ -0,0 +1,76 @@
+use chrono::Weekday;
+use clap::{Arg, ArgAction, ArgMatches, Command as App};
+
+use vcontrol::{Optolink, VControl, Value};
+
+pub fn app() -> App {
+  App::new("schedule")
+    .about("show or edit a schedule, e.g. `mon-fri 06:00-08:00,17:00-22:00; sat,sun 08:00-23:00`")
+    .arg(Arg::new("command").help("name of the command").required(true))
+    .subcommand(
+      App::new("set")
+        .about("set the time spans of the given days, leaving all other days unchanged")
+        .arg(Arg::new("days").help("days, e.g. `mon-fri` or `sat,sun`").required(true))
+        .arg(Arg::new("spans").help("time spans, e.g. `06:00-08:00,17:00-22:00` or `off`").required(true)),
+    )
+    .subcommand(
+      App::new("copy")
+        .about("copy the time spans of one day to other days")
+        .arg(Arg::new("from").help("source day").required(true))
+        .arg(Arg::new("to").help("target days").action(ArgAction::Append).required(true)),
+    )
+    .subcommand(
+      App::new("clear")
+        .about("clear the time spans of the given days, or of all days")
+        .arg(Arg::new("days").help("days to clear").action(ArgAction::Append)),
+    )
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+  day.parse().map_err(|_| format!("Invalid day “{day}”."))
+}
+
+pub async fn schedule(optolink: Optolink, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+  let mut vcontrol = VControl::connect(optolink).await?;
+
+  log::info!("Connected to '{}' via {} protocol.", vcontrol.device().name(), vcontrol.protocol());
+
+  let command = matches.get_one::<String>("command").unwrap();
+
+  let mut circuit_times = match vcontrol.get(command).await?.value {
+    Value::CircuitTimes(circuit_times) => *circuit_times,
+    value => return Err(format!("Command '{command}' is not a schedule, got {value:?}.").into()),
+  };
+
+  match matches.subcommand() {
+    Some(("set", matches)) => {
+      let days = matches.get_one::<String>("days").unwrap();
+      let spans = matches.get_one::<String>("spans").unwrap();
+      circuit_times.update(&format!("{days} {spans}"))?;
+    },
+    Some(("copy", matches)) => {
+      let from = parse_day(matches.get_one::<String>("from").unwrap())?;
+
+      for to in matches.get_many::<String>("to").unwrap() {
+        circuit_times.copy_day(from, parse_day(to)?);
+      }
+    },
+    Some(("clear", matches)) => match matches.get_many::<String>("days") {
+      Some(days) => {
+        for day in days {
+          circuit_times.clear_day(parse_day(day)?);
+        }
+      },
+      None => circuit_times.clear(),
+    },
+    _ => {
+      println!("{circuit_times}");
+      return Ok(());
+    },
+  }
+
+  vcontrol.set(command, Value::CircuitTimes(Box::new(circuit_times.clone()))).await?;
+  println!("{circuit_times}");
+
+  Ok(())
+}
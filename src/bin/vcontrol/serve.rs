@@ -0,0 +1,308 @@
+use std::{collections::BTreeMap, net::SocketAddr};
+
+use axum::{
+  Json, Router,
+  extract::{Path, State},
+  http::StatusCode,
+  response::{IntoResponse, Response},
+  routing::get,
+};
+use clap::{Arg, ArgAction, ArgMatches, Command as App, value_parser};
+use schemars::{JsonSchema, r#gen::SchemaSettings};
+use serde::Serialize;
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use vcontrol::{AccessMode, Command, DataType, Error, Mapping, Optolink, Translation, VControl, VControlHandle, Value};
+
+pub fn app() -> App {
+  App::new("serve").about("serve a REST API").arg(
+    Arg::new("listen")
+      .long("listen")
+      .action(ArgAction::Set)
+      .value_parser(value_parser!(SocketAddr))
+      .default_value("127.0.0.1:8080")
+      .help("address to listen on"),
+  )
+}
+
+/// Metadata of a command.
+#[derive(Serialize, JsonSchema)]
+struct CommandInfo {
+  name: &'static str,
+  #[schemars(with = "String")]
+  access_mode: AccessMode,
+  #[schemars(with = "String")]
+  data_type: DataType,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  unit: Option<&'static str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  lower_bound: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  upper_bound: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  stepping: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schemars(with = "Option<BTreeMap<i32, String>>")]
+  mapping: Option<&'static Mapping>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  default_value: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schemars(with = "Option<String>")]
+  description: Option<Translation>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  priority: Option<u8>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  url: Option<&'static str>,
+}
+
+impl CommandInfo {
+  fn new(name: &'static str, command: &'static Command) -> Self {
+    Self {
+      name,
+      access_mode: command.access_mode(),
+      data_type: command.data_type(),
+      unit: command.unit(),
+      lower_bound: command.lower_bound(),
+      upper_bound: command.upper_bound(),
+      stepping: command.stepping(),
+      mapping: command.mapping(),
+      default_value: command.default_value(),
+      description: command.description(),
+      priority: command.priority(),
+      url: command.url(),
+    }
+  }
+}
+
+/// The value of a command together with its metadata.
+#[derive(Serialize, JsonSchema)]
+struct CommandValue {
+  value: Value,
+  #[serde(flatten)]
+  info: CommandInfo,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ErrorResponse {
+  error: String,
+}
+
+struct ApiError(StatusCode, String);
+
+impl From<Error> for ApiError {
+  fn from(err: Error) -> Self {
+    let status = match err {
+      Error::UnsupportedCommand(_) => StatusCode::NOT_FOUND,
+      Error::UnsupportedMode(_) => StatusCode::METHOD_NOT_ALLOWED,
+      Error::InvalidArgument(_) | Error::UnsupportedConversion(_) | Error::UnknownEnumVariant(_) => {
+        StatusCode::BAD_REQUEST
+      },
+      Error::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+      _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    Self(status, err.to_string())
+  }
+}
+
+impl IntoResponse for ApiError {
+  fn into_response(self) -> Response {
+    (self.0, Json(ErrorResponse { error: self.1 })).into_response()
+  }
+}
+
+fn commands(handle: &VControlHandle) -> BTreeMap<&'static str, &'static Command> {
+  vcontrol::commands::system_commands()
+    .entries()
+    .chain(handle.device().commands().entries())
+    .map(|(name, command)| (*name, *command))
+    .collect()
+}
+
+fn command(handle: &VControlHandle, name: &str) -> Result<(&'static str, &'static Command), ApiError> {
+  vcontrol::commands::system_commands()
+    .get_entry(name)
+    .or_else(|| handle.device().commands().get_entry(name))
+    .map(|(name, command)| (*name, *command))
+    .ok_or_else(|| Error::UnsupportedCommand(name.to_owned()).into())
+}
+
+async fn list_commands(State(handle): State<VControlHandle>) -> Json<Vec<CommandInfo>> {
+  Json(commands(&handle).into_iter().map(|(name, command)| CommandInfo::new(name, command)).collect())
+}
+
+async fn get_command(
+  State(handle): State<VControlHandle>,
+  Path(name): Path<String>,
+) -> Result<Json<CommandValue>, ApiError> {
+  let (name, command) = command(&handle, &name)?;
+  let output_value = handle.get(name).await?;
+  Ok(Json(CommandValue { value: output_value.value, info: CommandInfo::new(name, command) }))
+}
+
+async fn set_command(
+  State(handle): State<VControlHandle>,
+  Path(name): Path<String>,
+  Json(input): Json<Value>,
+) -> Result<StatusCode, ApiError> {
+  let (name, _) = command(&handle, &name)?;
+  handle.set(name, input).await?;
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn openapi() -> serde_json::Value {
+  let mut generator = SchemaSettings::openapi3().into_generator();
+
+  let command_list = generator.subschema_for::<Vec<CommandInfo>>();
+  let command_value = generator.subschema_for::<CommandValue>();
+  let value = generator.subschema_for::<Value>();
+  let error = generator.subschema_for::<ErrorResponse>();
+
+  let name = json!({ "name": "name", "in": "path", "required": true, "schema": { "type": "string" } });
+  let error_response = json!({ "description": "error", "content": { "application/json": { "schema": error } } });
+
+  json!({
+    "openapi": "3.0.3",
+    "info": { "title": "vcontrol", "version": env!("CARGO_PKG_VERSION") },
+    "paths": {
+      "/commands": {
+        "get": {
+          "summary": "List all commands.",
+          "responses": {
+            "200": { "description": "all commands", "content": { "application/json": { "schema": command_list } } },
+          },
+        },
+      },
+      "/commands/{name}": {
+        "get": {
+          "summary": "Get the value of a command.",
+          "parameters": [name],
+          "responses": {
+            "200": { "description": "command value", "content": { "application/json": { "schema": command_value } } },
+            "default": error_response,
+          },
+        },
+        "put": {
+          "summary": "Set the value of a command.",
+          "parameters": [name],
+          "requestBody": { "required": true, "content": { "application/json": { "schema": value } } },
+          "responses": {
+            "204": { "description": "value set" },
+            "default": error_response,
+          },
+        },
+      },
+    },
+    "components": {
+      "schemas": generator.take_definitions(),
+    },
+  })
+}
+
+pub fn router(handle: VControlHandle) -> Router {
+  let openapi = openapi();
+
+  Router::new()
+    .route("/commands", get(list_commands))
+    .route("/commands/{name}", get(get_command).put(set_command))
+    .route("/openapi.json", get(move || async move { Json(openapi) }))
+    .with_state(handle)
+}
+
+pub async fn serve(optolink: Optolink, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+  let mut vcontrol = VControl::connect(optolink).await?;
+
+  log::info!("Connected to '{}' via {} protocol.", vcontrol.device().name(), vcontrol.protocol());
+
+  // All requests are serialised through the handle's request queue.
+  let handle = VControlHandle::spawn(vcontrol);
+
+  let listen = matches.get_one::<SocketAddr>("listen").unwrap();
+  let listener = TcpListener::bind(listen).await?;
+  log::info!("Listening on http://{}.", listener.local_addr()?);
+
+  axum::serve(listener, router(handle)).await?;
+
+  Ok(())
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+  use super::*;
+  use crate::simulated;
+
+  use axum::{
+    body::Body,
+    http::{Method, Request},
+  };
+  use http_body_util::BodyExt;
+  use tower::ServiceExt;
+  use vcontrol::{Device, sim::Simulator};
+
+  async fn request(router: Router, method: Method, uri: &str, body: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+      Some(body) => request.header("content-type", "application/json").body(Body::from(body.to_owned())),
+      None => request.body(Body::empty()),
+    };
+
+    let response = router.oneshot(request.unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = if bytes.is_empty() { serde_json::Value::Null } else { serde_json::from_slice(&bytes).unwrap() };
+    (status, json)
+  }
+
+  #[tokio::test]
+  async fn get_and_put_command() {
+    let simulator = Simulator::from_device(Device::by_name("VScotHO1_4").unwrap());
+    // `TiefpassTemperaturwert_ATS`
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+    let router = router(simulated(simulator.clone()).await);
+
+    let (status, json) = request(router.clone(), Method::GET, "/commands/TiefpassTemperaturwert_ATS", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "TiefpassTemperaturwert_ATS");
+    assert_eq!(json["value"], -3.5);
+
+    let (status, _) = request(router.clone(), Method::PUT, "/commands/KD3_KonfiNeigungA1", Some("1.5")).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let mut buf = [0];
+    simulator.read(0x27D3, &mut buf);
+    assert_eq!(buf, [15]);
+
+    let (status, json) = request(router.clone(), Method::GET, "/commands/KD3_KonfiNeigungA1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["value"], 1.5);
+  }
+
+  #[tokio::test]
+  async fn command_errors() {
+    let simulator = Simulator::from_device(Device::by_name("VScotHO1_4").unwrap());
+    let router = router(simulated(simulator).await);
+
+    let (status, json) = request(router.clone(), Method::GET, "/commands/Unbekannt", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].is_string());
+
+    let (status, _) = request(router.clone(), Method::PUT, "/commands/TiefpassTemperaturwert_ATS", Some("1.5")).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+    let (status, _) = request(router.clone(), Method::PUT, "/commands/KD3_KonfiNeigungA1", Some("\"steep\"")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn openapi_json() {
+    let simulator = Simulator::from_device(Device::by_name("VScotHO1_4").unwrap());
+    let router = router(simulated(simulator).await);
+
+    let (status, json) = request(router, Method::GET, "/openapi.json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["openapi"], "3.0.3");
+    assert!(json["paths"]["/commands/{name}"]["get"].is_object());
+    assert!(json["paths"]["/commands/{name}"]["put"].is_object());
+    assert!(json["components"]["schemas"]["CommandValue"].is_object());
+  }
+}
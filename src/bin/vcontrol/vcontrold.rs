@@ -0,0 +1,278 @@
+use std::net::SocketAddr;
+
+use clap::{Arg, ArgAction, ArgMatches, Command as App, value_parser};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+  net::{TcpListener, TcpStream},
+};
+
+use vcontrol::{Command, Locale, Optolink, VControl, VControlHandle};
+
+const PROMPT: &str = "vctrld>";
+
+pub fn app() -> App {
+  App::new("vcontrold").about("serve the vcontrold text protocol").arg(
+    Arg::new("listen")
+      .long("listen")
+      .action(ArgAction::Set)
+      .value_parser(value_parser!(SocketAddr))
+      .default_value("127.0.0.1:3002")
+      .help("address to listen on"),
+  )
+}
+
+fn command(handle: &VControlHandle, name: &str) -> Option<(&'static str, &'static Command)> {
+  vcontrol::commands::system_commands()
+    .get_entry(name)
+    .or_else(|| handle.device().commands().get_entry(name))
+    .map(|(name, command)| (*name, *command))
+}
+
+/// Finds a command by name, also accepting the `get`/`set` prefixes used by `vcontrold` command names.
+fn prefixed_command(handle: &VControlHandle, name: &str, prefix: &str) -> Option<(&'static str, &'static Command)> {
+  command(handle, name).or_else(|| {
+    let name = name.strip_prefix(prefix)?;
+    command(handle, name).or_else(|| {
+      // Also accept a lower-case first letter, e.g. `getBetriebsart` for `betriebsart`.
+      let mut chars = name.chars();
+      let first = chars.next()?;
+      command(handle, &format!("{}{}", first.to_lowercase(), chars.as_str()))
+    })
+  })
+}
+
+fn detail(name: &str, command: &Command) -> String {
+  let mut detail = format!("{name}:\n");
+  detail.push_str(&format!("Access: {}\n", serde_json::to_string(&command.access_mode()).unwrap().trim_matches('"')));
+  detail.push_str(&format!("Type: {:?}\n", command.data_type()));
+
+  if let Some(unit) = command.unit() {
+    detail.push_str(&format!("Unit: {unit}\n"));
+  }
+
+  if let Some(lower_bound) = command.lower_bound() {
+    detail.push_str(&format!("Min: {lower_bound}\n"));
+  }
+
+  if let Some(upper_bound) = command.upper_bound() {
+    detail.push_str(&format!("Max: {upper_bound}\n"));
+  }
+
+  if let Some(stepping) = command.stepping() {
+    detail.push_str(&format!("Step: {stepping}\n"));
+  }
+
+  if let Some(mapping) = command.mapping() {
+    let mut entries = mapping.labels(Locale::default()).entries().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| **key);
+
+    for (key, label) in entries {
+      detail.push_str(&format!("Enum: {key} = {label}\n"));
+    }
+  }
+
+  detail
+}
+
+/// Executes a single line and returns the response, or `None` if the connection should be closed.
+async fn execute(handle: &VControlHandle, line: &str) -> Option<String> {
+  let mut parts = line.split_whitespace();
+  let Some(cmd) = parts.next() else {
+    return Some(String::new());
+  };
+  let args = parts.collect::<Vec<_>>();
+
+  let response = match (cmd, args.as_slice()) {
+    ("quit" | "close", _) => return None,
+    ("help", _) => [
+      "close, quit: close the connection",
+      "commands: list all commands",
+      "detail <command>: show details of a command",
+      "device: show the connected device",
+      "protocol: show the used protocol",
+      "version: show the version",
+      "<command>, get <command>: get the value of a command",
+      "set<command> <value>, set <command> <value>: set the value of a command",
+    ]
+    .join("\n"),
+    ("commands", _) => {
+      let mut names = vcontrol::commands::system_commands()
+        .keys()
+        .chain(handle.device().commands().keys())
+        .map(|name| {
+          let access_mode = command(handle, name).unwrap().1.access_mode();
+          format!("{name}: {}", serde_json::to_string(&access_mode).unwrap().trim_matches('"'))
+        })
+        .collect::<Vec<_>>();
+      names.sort();
+      names.join("\n")
+    },
+    ("detail", [name]) => match prefixed_command(handle, name, "get") {
+      Some((name, command)) => detail(name, command).trim_end().to_owned(),
+      None => format!("ERR: command {name} unknown"),
+    },
+    ("device", []) => handle.device().name().to_owned(),
+    ("protocol", []) => handle.protocol().to_string(),
+    ("version", []) => format!("vcontrol {}", env!("CARGO_PKG_VERSION")),
+    ("get", [name]) => get(handle, name).await,
+    ("set", [name, input @ ..]) if !input.is_empty() => set(handle, name, "", &input.join(" ")).await,
+    (name, []) if prefixed_command(handle, name, "get").is_some() => get(handle, name).await,
+    (name, input)
+      if !input.is_empty() && name.starts_with("set") && prefixed_command(handle, name, "set").is_some() =>
+    {
+      set(handle, name, "set", &input.join(" ")).await
+    },
+    _ => format!("ERR: command {cmd} unknown"),
+  };
+
+  Some(response)
+}
+
+async fn get(handle: &VControlHandle, name: &str) -> String {
+  let Some((name, _)) = prefixed_command(handle, name, "get") else {
+    return format!("ERR: command {name} unknown");
+  };
+
+  match handle.get(name).await {
+    Ok(value) => value.to_string(),
+    Err(err) => format!("ERR: {err}"),
+  }
+}
+
+async fn set(handle: &VControlHandle, name: &str, prefix: &str, input: &str) -> String {
+  let Some((name, _)) = prefixed_command(handle, name, prefix) else {
+    return format!("ERR: command {name} unknown");
+  };
+
+  // Mapping labels are looked up when setting the value.
+  match handle.set(name, serde_json::from_str(input).unwrap_or_else(|_| input.parse().unwrap())).await {
+    Ok(()) => "OK".to_owned(),
+    Err(err) => format!("ERR: {err}"),
+  }
+}
+
+async fn client(handle: VControlHandle, stream: TcpStream) -> Result<(), std::io::Error> {
+  let (reader, mut writer) = stream.into_split();
+  let mut lines = BufReader::new(reader).lines();
+
+  writer.write_all(PROMPT.as_bytes()).await?;
+
+  while let Some(line) = lines.next_line().await? {
+    let Some(response) = execute(&handle, line.trim()).await else {
+      writer.write_all(b"good bye!\n").await?;
+      break;
+    };
+
+    if !response.is_empty() {
+      writer.write_all(response.as_bytes()).await?;
+      writer.write_all(b"\n").await?;
+    }
+    writer.write_all(PROMPT.as_bytes()).await?;
+  }
+
+  Ok(())
+}
+
+pub async fn vcontrold(optolink: Optolink, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+  let mut vcontrol = VControl::connect(optolink).await?;
+
+  log::info!("Connected to '{}' via {} protocol.", vcontrol.device().name(), vcontrol.protocol());
+
+  // All clients share the same connection, requests are serialised through the handle's request queue.
+  let handle = VControlHandle::spawn(vcontrol);
+
+  let listen = matches.get_one::<SocketAddr>("listen").unwrap();
+  let listener = TcpListener::bind(listen).await?;
+  log::info!("Listening on {}.", listener.local_addr()?);
+
+  loop {
+    let (stream, addr) = listener.accept().await?;
+    log::info!("Client {addr} connected.");
+
+    let handle = handle.clone();
+    tokio::spawn(async move {
+      if let Err(err) = client(handle, stream).await {
+        log::warn!("Client {addr} error: {err}");
+      }
+
+      log::info!("Client {addr} disconnected.");
+    });
+  }
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+  use super::*;
+  use crate::simulated;
+
+  use vcontrol::{Device, sim::Simulator};
+
+  async fn handle() -> (Simulator, VControlHandle) {
+    let simulator = Simulator::from_device(Device::by_name("VScotHO1_4").unwrap());
+    // `TiefpassTemperaturwert_ATS`
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+    let handle = simulated(simulator.clone()).await;
+    (simulator, handle)
+  }
+
+  #[tokio::test]
+  async fn quit() {
+    let (_, handle) = handle().await;
+
+    assert_eq!(execute(&handle, "quit").await, None);
+    assert_eq!(execute(&handle, "close").await, None);
+    assert_eq!(execute(&handle, "").await, Some(String::new()));
+  }
+
+  #[tokio::test]
+  async fn get() {
+    let (_, handle) = handle().await;
+
+    for line in ["get TiefpassTemperaturwert_ATS", "getTiefpassTemperaturwert_ATS", "TiefpassTemperaturwert_ATS"] {
+      let response = execute(&handle, line).await.unwrap();
+      assert!(response.starts_with("-3.5"), "{line}: {response}");
+    }
+
+    assert_eq!(execute(&handle, "get Unbekannt").await.unwrap(), "ERR: command Unbekannt unknown");
+    assert_eq!(execute(&handle, "getUnbekannt").await.unwrap(), "ERR: command getUnbekannt unknown");
+  }
+
+  #[tokio::test]
+  async fn set() {
+    let (simulator, handle) = handle().await;
+
+    assert_eq!(execute(&handle, "set KD3_KonfiNeigungA1 1.5").await.unwrap(), "OK");
+    let mut buf = [0];
+    simulator.read(0x27D3, &mut buf);
+    assert_eq!(buf, [15]);
+
+    assert_eq!(execute(&handle, "setKD3_KonfiNeigungA1 0.5").await.unwrap(), "OK");
+    simulator.read(0x27D3, &mut buf);
+    assert_eq!(buf, [5]);
+
+    let response = execute(&handle, "set TiefpassTemperaturwert_ATS 1").await.unwrap();
+    assert!(response.starts_with("ERR: "), "{response}");
+
+    assert_eq!(execute(&handle, "set KD3_KonfiNeigungA1").await.unwrap(), "ERR: command set unknown");
+    assert_eq!(execute(&handle, "set Unbekannt 1").await.unwrap(), "ERR: command Unbekannt unknown");
+  }
+
+  #[tokio::test]
+  async fn commands_and_detail() {
+    let (_, handle) = handle().await;
+
+    let commands = execute(&handle, "commands").await.unwrap();
+    assert!(commands.lines().any(|line| line.starts_with("TiefpassTemperaturwert_ATS: ")), "{commands}");
+    assert!(commands.lines().any(|line| line.starts_with("device_id: ")), "{commands}");
+
+    let detail = execute(&handle, "detail getKD3_KonfiNeigungA1").await.unwrap();
+    assert!(detail.starts_with("KD3_KonfiNeigungA1:\nAccess: "), "{detail}");
+
+    assert_eq!(execute(&handle, "detail Unbekannt").await.unwrap(), "ERR: command Unbekannt unknown");
+    assert_eq!(execute(&handle, "detail").await.unwrap(), "ERR: command detail unknown");
+
+    assert_eq!(execute(&handle, "device").await.unwrap(), "VScotHO1_4");
+    assert_eq!(execute(&handle, "protocol").await.unwrap(), handle.protocol().to_string());
+    assert_eq!(execute(&handle, "frobnicate").await.unwrap(), "ERR: command frobnicate unknown");
+  }
+}
@@ -1,7 +1,7 @@
 use arrayref::array_ref;
 
 use crate::{
-  AccessMode, DataType, Error, Optolink, Parameter, Value,
+  AccessMode, DataType, Error, Function, Locale, Mapping, Optolink, Parameter, Translation, Value,
   conversion::Conversion,
   protocol::Protocol,
   types::{self, CircuitTimes, Date, DateTime, DeviceId, DeviceIdF0},
@@ -12,6 +12,8 @@ use crate::{
 pub struct Command {
   pub(crate) addr: u16,
   pub(crate) mode: AccessMode,
+  pub(crate) read_function: Function,
+  pub(crate) write_function: Function,
   pub(crate) data_type: DataType,
   pub(crate) parameter: Parameter,
   pub(crate) block_count: Option<usize>,
@@ -23,8 +25,15 @@ pub struct Command {
   pub(crate) conversion: Option<Conversion>,
   pub(crate) lower_bound: Option<f64>,
   pub(crate) upper_bound: Option<f64>,
+  pub(crate) stepping: Option<f64>,
   pub(crate) unit: Option<&'static str>,
-  pub(crate) mapping: Option<phf::map::Map<i32, &'static str>>,
+  pub(crate) mapping: Option<Mapping>,
+  /// The factory default from the datapoint definitions, parsed according to `data_type` when requested.
+  pub(crate) default_value: Option<&'static str>,
+  pub(crate) description: Option<Translation>,
+  pub(crate) priority: Option<u8>,
+  pub(crate) filter_criterion: Option<bool>,
+  pub(crate) url: Option<&'static str>,
 }
 
 impl Command {
@@ -38,6 +47,16 @@ impl Command {
     self.mode
   }
 
+  /// Returns the function used for reading the command, e.g. `Function::GfaRead`.
+  pub fn read_function(&self) -> Function {
+    self.read_function
+  }
+
+  /// Returns the function used for writing the command, e.g. `Function::GfaWrite`.
+  pub fn write_function(&self) -> Function {
+    self.write_function
+  }
+
   /// Get the command's data type.
   pub fn data_type(&self) -> DataType {
     self.data_type
@@ -68,11 +87,52 @@ impl Command {
     self.upper_bound
   }
 
+  /// Returns the step size for the command value.
+  pub fn stepping(&self) -> Option<f64> {
+    self.stepping
+  }
+
   /// Returns the mapping for the command value.
-  pub fn mapping(&self) -> Option<&phf::map::Map<i32, &'static str>> {
+  pub fn mapping(&self) -> Option<&Mapping> {
     self.mapping.as_ref()
   }
 
+  /// Returns the factory default for the command value.
+  pub fn default_value(&self) -> Option<Value> {
+    let default_value = self.default_value?;
+
+    Some(match self.data_type {
+      DataType::Double => Value::Double(default_value.parse().ok()?),
+      DataType::Int | DataType::Byte => Value::Int(default_value.parse().ok()?),
+      DataType::Date => Value::Date(default_value.parse().ok()?),
+      DataType::DateTime => Value::DateTime(default_value.parse().ok()?),
+      DataType::ByteArray => Value::ByteArray(
+        default_value.split_whitespace().map(|b| u8::from_str_radix(b, 16)).collect::<Result<_, _>>().ok()?,
+      ),
+      _ => Value::String(default_value.to_owned()),
+    })
+  }
+
+  /// Returns the help text for the command.
+  pub fn description(&self) -> Option<Translation> {
+    self.description
+  }
+
+  /// Returns the display priority of the command in Vitosoft.
+  pub fn priority(&self) -> Option<u8> {
+    self.priority
+  }
+
+  /// Returns whether the command is used as a filter criterion in Vitosoft.
+  pub fn filter_criterion(&self) -> Option<bool> {
+    self.filter_criterion
+  }
+
+  /// Returns the URL of the command documentation.
+  pub fn url(&self) -> Option<&'static str> {
+    self.url
+  }
+
   /// Deserializes the command value from bytes.
   pub fn deserialize(&self, bytes: &[u8]) -> Result<Value, Error> {
     if let Some(block_count) = self.block_count {
@@ -102,6 +162,21 @@ impl Command {
       return Ok(Value::Empty);
     }
 
+    // These conversions decode the raw bytes regardless of the data type.
+    if let Some(
+      conversion @ (Conversion::IpAddress
+      | Conversion::DayMonthBcd
+      | Conversion::DayToDate
+      | Conversion::HexByteToUtf16Byte
+      | Conversion::HexByteToDecimalByte
+      | Conversion::FixedStringTerminalZeroes),
+    ) = &self.conversion
+    {
+      return Value::ByteArray(bytes.to_vec())
+        .convert(conversion)
+        .map_err(|err| Error::InvalidFormat(format!("failed to convert 0x{:04X}: {err}", self.addr)));
+    }
+
     let mut value = match &self.data_type {
       DataType::DeviceId => {
         if bytes.len() != 8 {
@@ -283,25 +358,95 @@ impl Command {
   pub async fn get(&self, o: &mut Optolink, protocol: Protocol) -> Result<Value, Error> {
     log::trace!("Command::get(…)");
 
+    self.check_readable()?;
+
+    let buf = self.read_block(o, protocol).await?;
+
+    self.deserialize_block(&buf)
+  }
+
+  pub(crate) fn check_readable(&self) -> Result<(), Error> {
     if !self.mode.is_read() {
       return Err(Error::UnsupportedMode(format!("Address 0x{:04X} does not support reading.", self.addr)));
     }
 
-    let mut buf = vec![0; self.block_len];
-    protocol.get(o, self.addr, &mut buf).await?;
-
-    let bytes = &buf[self.byte_pos..(self.byte_pos + self.byte_len)];
+    Ok(())
+  }
 
-    self.deserialize(bytes)
+  /// Deserializes the command value from a whole block of `block_len` bytes starting at `addr`.
+  pub(crate) fn deserialize_block(&self, block: &[u8]) -> Result<Value, Error> {
+    self.deserialize(&block[self.byte_pos..(self.byte_pos + self.byte_len)])
   }
 
-  pub async fn set(&self, o: &mut Optolink, protocol: Protocol, mut input: Value) -> Result<(), Error> {
+  pub async fn set(&self, o: &mut Optolink, protocol: Protocol, input: Value) -> Result<(), Error> {
     log::trace!("Command::set(…)");
 
     if !self.mode.is_write() {
       return Err(Error::UnsupportedMode(format!("Address 0x{:04X} does not support writing.", self.addr)));
     }
 
+    if let Some(block_count) = self.block_count {
+      let values = match input {
+        Value::Array(values) => values,
+        // Arrays of small integers are deserialized as byte arrays.
+        Value::ByteArray(bytes) => bytes.into_iter().map(|b| Value::Int(b.into())).collect(),
+        input => {
+          return Err(Error::InvalidArgument(format!("expected array of {block_count} values, got {input:?}")));
+        },
+      };
+
+      if values.len() != block_count {
+        return Err(Error::InvalidArgument(format!(
+          "expected array of {} values, got {} values",
+          block_count,
+          values.len()
+        )));
+      }
+
+      let block_len = self.block_len / block_count;
+
+      let mut bytes = Vec::with_capacity(self.block_len);
+      for value in values {
+        let mut block = match value {
+          // Empty values are read from blocks containing only `0xFF`.
+          Value::Empty => vec![0xff; block_len],
+          value => match self.encode(value, block_len)? {
+            Encoded::Bytes(bytes) => bytes,
+            Encoded::Bits { .. } => return Err(unsupported_parameter(self.data_type, &self.parameter)),
+          },
+        };
+
+        if block.len() > block_len {
+          return Err(Error::InvalidArgument(format!(
+            "{} bytes exceed the array element length of {} bytes.",
+            block.len(),
+            block_len
+          )));
+        }
+        block.resize(block_len, 0);
+
+        bytes.extend(block);
+      }
+
+      return self.write_value(o, protocol, &bytes).await;
+    }
+
+    match self.encode(input, self.byte_len)? {
+      Encoded::Bytes(bytes) => self.write_value(o, protocol, &bytes).await,
+      Encoded::Bits { bit_len, n } => self.write_bits(o, protocol, bit_len, n).await,
+    }
+  }
+
+  /// Encodes a single value, converting it back and checking its bounds.
+  ///
+  /// Values of conversions operating on bytes are padded to `len`.
+  fn encode(&self, mut input: Value, len: usize) -> Result<Encoded, Error> {
+    if let Value::String(ref label) = input
+      && let Some(mapping) = &self.mapping
+    {
+      input = Value::Int(self.mapping_key(mapping, label)?.into());
+    }
+
     match &input {
       Value::Int(n) => {
         if let Some(lower_bound) = self.lower_bound
@@ -315,6 +460,8 @@ impl Command {
         {
           return Err(Error::InvalidArgument(format!("{} is greater than maximum {}.", n, upper_bound)));
         }
+
+        self.check_stepping(*n as f64)?;
       },
       Value::Double(n) => {
         if let Some(lower_bound) = self.lower_bound
@@ -328,19 +475,47 @@ impl Command {
         {
           return Err(Error::InvalidArgument(format!("{} is greater than maximum {}", n, upper_bound)));
         }
+
+        self.check_stepping(*n)?;
       },
       _ => (),
     }
 
     if let Some(conversion) = &self.conversion {
-      input = input.convert_back(conversion).unwrap();
+      input = input.convert_back(conversion).map_err(|err| Error::UnsupportedConversion(err.to_string()))?;
+
+      // Conversions operating on bytes are written as is, padded to the full length.
+      if let Value::ByteArray(mut bytes) = input {
+        if bytes.len() > len {
+          return Err(Error::InvalidArgument(format!(
+            "{} bytes exceed the length of 0x{:04X} of {} bytes.",
+            bytes.len(),
+            self.addr,
+            len
+          )));
+        }
+
+        // Trailing zeroes are stripped when reading, so pad with them.
+        bytes.resize(len, 0);
+
+        return Ok(Encoded::Bytes(bytes));
+      }
+    }
+
+    if let Some(bit_len) = self.bit_len {
+      return match input {
+        Value::Int(n) => Ok(Encoded::Bits { bit_len, n }),
+        Value::Double(n) => Ok(Encoded::Bits { bit_len, n: n.round() as i64 }),
+        input => Err(Error::InvalidArgument(format!("expected {:?}, got {:?}", self.data_type, input))),
+      };
     }
 
+    // Converting back may introduce rounding errors, e.g. `0.3 / 0.1`.
     if self.data_type == DataType::Int
       && let Value::Double(n) = input
-      && n.fract() == 0.0
+      && (n - n.round()).abs() < 1e-6
     {
-      input = Value::Int(n as i64);
+      input = Value::Int(n.round() as i64);
     }
 
     if self.data_type == DataType::DateTime
@@ -357,6 +532,12 @@ impl Command {
       input = Value::Date(date);
     }
 
+    if self.data_type == DataType::CircuitTimes
+      && let Value::String(ref s) = input
+    {
+      input = Value::CircuitTimes(Box::new(s.parse::<CircuitTimes>()?));
+    }
+
     let bytes = match (self.data_type, input) {
       (DataType::Date, Value::Date(date)) => date.to_bytes().to_vec(),
       (DataType::DateTime, Value::DateTime(date_time)) => date_time.to_bytes().to_vec(),
@@ -375,50 +556,248 @@ impl Command {
         Parameter::SIntHighByteFirst => (n as i16).to_be_bytes().to_vec(),
         Parameter::SInt4 => (n as i32).to_le_bytes().to_vec(),
         Parameter::SInt4HighByteFirst => (n as i32).to_be_bytes().to_vec(),
-        _ => unreachable!(),
+        _ => return Err(unsupported_parameter(self.data_type, &self.parameter)),
       },
-      (DataType::Double, Value::Double(n)) => match self.parameter {
-        Parameter::Byte => (n as u8).to_le_bytes().to_vec(),
-        Parameter::Int => (n as u16).to_le_bytes().to_vec(),
-        Parameter::IntHighByteFirst => (n as u16).to_be_bytes().to_vec(),
-        Parameter::Int4 => (n as u32).to_le_bytes().to_vec(),
-        Parameter::Int4HighByteFirst => (n as u32).to_be_bytes().to_vec(),
-        Parameter::SByte => (n as i8).to_le_bytes().to_vec(),
-        Parameter::SInt => (n as i16).to_le_bytes().to_vec(),
-        Parameter::SIntHighByteFirst => (n as i16).to_be_bytes().to_vec(),
-        Parameter::SInt4 => (n as i32).to_le_bytes().to_vec(),
-        Parameter::SInt4HighByteFirst => (n as i32).to_be_bytes().to_vec(),
-        _ => unreachable!(),
+      (DataType::Double, Value::Double(n)) => {
+        // Converting back may result in fractions, e.g. `2.3 * 10.0`.
+        let n = n.round();
+
+        match self.parameter {
+          Parameter::Byte => (n as u8).to_le_bytes().to_vec(),
+          Parameter::Int => (n as u16).to_le_bytes().to_vec(),
+          Parameter::IntHighByteFirst => (n as u16).to_be_bytes().to_vec(),
+          Parameter::Int4 => (n as u32).to_le_bytes().to_vec(),
+          Parameter::Int4HighByteFirst => (n as u32).to_be_bytes().to_vec(),
+          Parameter::SByte => (n as i8).to_le_bytes().to_vec(),
+          Parameter::SInt => (n as i16).to_le_bytes().to_vec(),
+          Parameter::SIntHighByteFirst => (n as i16).to_be_bytes().to_vec(),
+          Parameter::SInt4 => (n as i32).to_le_bytes().to_vec(),
+          Parameter::SInt4HighByteFirst => (n as i32).to_be_bytes().to_vec(),
+          _ => return Err(unsupported_parameter(self.data_type, &self.parameter)),
+        }
       },
       (data_type, input) => return Err(Error::InvalidArgument(format!("expected {:?}, got {:?}", data_type, input))),
     };
 
-    protocol.set(o, self.addr, &bytes).await.map_err(Into::into)
+    Ok(Encoded::Bytes(bytes))
+  }
+
+  /// Checks that the value is a multiple of the step size, counting from the lower bound.
+  fn check_stepping(&self, n: f64) -> Result<(), Error> {
+    let Some(stepping) = self.stepping else { return Ok(()) };
+
+    let origin = self.lower_bound.unwrap_or(0.0);
+    let steps = (n - origin) / stepping;
+
+    // Allow for rounding errors, e.g. `0.1 + 0.2`.
+    if (steps - steps.round()).abs() < 1e-6 {
+      return Ok(());
+    }
+
+    let round = |n: f64| (n * 1e9).round() / 1e9;
+    let lower = round(origin + steps.floor() * stepping);
+    let upper = round(origin + steps.ceil() * stepping);
+
+    Err(Error::InvalidArgument(format!(
+      "{} does not match step size {} of 0x{:04X}, nearest valid values are {} and {}.",
+      n, stepping, self.addr, lower, upper
+    )))
   }
+
+  /// Looks up the key for the given mapping label in any locale, ignoring case.
+  fn mapping_key(&self, mapping: &Mapping, label: &str) -> Result<i32, Error> {
+    // Some mappings contain duplicate labels, so always use the lowest key.
+    let mut entries = Vec::new();
+    for locale in Locale::ALL {
+      let mut locale_entries = mapping.labels(locale).entries().map(|(key, label)| (*key, *label)).collect::<Vec<_>>();
+      locale_entries.sort_by_key(|(key, _)| *key);
+      entries.extend(locale_entries);
+    }
+
+    let lowercase_label = label.to_lowercase();
+    if let Some((key, _)) = entries.iter().find(|(_, l)| l.to_lowercase() == lowercase_label) {
+      return Ok(*key);
+    }
+
+    if let Ok(key) = label.parse::<i32>()
+      && mapping.labels(Locale::default()).contains_key(&key)
+    {
+      return Ok(key);
+    }
+
+    let mut labels = Vec::<&str>::new();
+    for (_, label) in entries {
+      if !labels.contains(&label) {
+        labels.push(label);
+      }
+    }
+
+    let mut message = format!("Unknown value “{label}” for 0x{:04X}", self.addr);
+
+    let max_distance = (lowercase_label.chars().count() / 3).max(2);
+    let mut suggestions = labels
+      .iter()
+      .map(|l| (l.to_lowercase(), *l))
+      .map(|(lowercase, l)| (edit_distance(&lowercase, &lowercase_label), lowercase.contains(&lowercase_label), l))
+      .filter(|(distance, contains, _)| *distance <= max_distance || *contains)
+      .collect::<Vec<_>>();
+    suggestions.sort_by_key(|(distance, _, _)| *distance);
+
+    if let Some((_, _, suggestion)) = suggestions.first() {
+      message.push_str(&format!(", did you mean “{suggestion}”?"));
+    } else {
+      message.push('.');
+    }
+
+    message.push_str(&format!(" Valid values are: {}", labels.join(", ")));
+
+    Err(Error::UnknownEnumVariant(message))
+  }
+
+  /// Reads the whole block of `block_len` bytes starting at `addr`.
+  pub(crate) async fn read_block(&self, o: &mut Optolink, protocol: Protocol) -> Result<Vec<u8>, Error> {
+    let mut block = vec![0; self.block_len];
+    protocol.request_unchecked(o, self.read_function, self.addr, &[], &mut block).await?;
+    Ok(block)
+  }
+
+  /// Writes the value bytes, reading and patching the block if the value only covers part of it.
+  async fn write_value(&self, o: &mut Optolink, protocol: Protocol, bytes: &[u8]) -> Result<(), Error> {
+    if self.byte_pos == 0 && bytes.len() == self.block_len {
+      return self.write_block(o, protocol, bytes).await;
+    }
+
+    if self.byte_pos + bytes.len() > self.block_len {
+      return Err(Error::InvalidArgument(format!(
+        "{} bytes at position {} exceed the block length of 0x{:04X} of {} bytes.",
+        bytes.len(),
+        self.byte_pos,
+        self.addr,
+        self.block_len
+      )));
+    }
+
+    let mut block = self.read_block(o, protocol).await?;
+    block[self.byte_pos..(self.byte_pos + bytes.len())].copy_from_slice(bytes);
+    self.write_block(o, protocol, &block).await
+  }
+
+  /// Reads the block, replaces the `bit_len` bits starting at `bit_pos` with `n` and writes the block back.
+  async fn write_bits(&self, o: &mut Optolink, protocol: Protocol, bit_len: usize, n: i64) -> Result<(), Error> {
+    if n < 0 || n >= 1 << bit_len {
+      return Err(Error::InvalidArgument(format!("{n} does not fit into {bit_len} bits.")));
+    }
+
+    let mut block = self.read_block(o, protocol).await?;
+
+    for i in 0..bit_len {
+      let bit_pos = self.bit_pos + i;
+
+      let byte = bit_pos / 8;
+      let bit = bit_pos % 8;
+      let bit_mask = 0b10000000 >> bit;
+
+      if (n >> (bit_len - 1 - i)) & 0b1 != 0 {
+        block[byte] |= bit_mask;
+      } else {
+        block[byte] &= !bit_mask;
+      }
+    }
+
+    self.write_block(o, protocol, &block).await
+  }
+
+  /// Writes the given bytes at `addr`, reading them back afterwards if `ProtocolConfig::verify_writes` is set.
+  async fn write_block(&self, o: &mut Optolink, protocol: Protocol, bytes: &[u8]) -> Result<(), Error> {
+    protocol.request_unchecked(o, self.write_function, self.addr, bytes, &mut []).await?;
+
+    if o.protocol_config().verify_writes {
+      let mut buf = vec![0; bytes.len()];
+      protocol.request_unchecked(o, self.read_function, self.addr, &[], &mut buf).await?;
+
+      if buf != bytes {
+        return Err(Error::VerificationFailed(format!(
+          "Wrote {:02X?} to 0x{:04X}, but read back {:02X?}.",
+          bytes, self.addr, buf
+        )));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Returns the Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b = b.chars().collect::<Vec<_>>();
+  let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+  for (i, ca) in a.chars().enumerate() {
+    let mut diagonal = row[0];
+    row[0] = i + 1;
+
+    for (j, cb) in b.iter().enumerate() {
+      let substitution = diagonal + usize::from(ca != *cb);
+      diagonal = row[j + 1];
+      row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+    }
+  }
+
+  row[b.len()]
+}
+
+/// A value encoded for writing.
+enum Encoded {
+  Bytes(Vec<u8>),
+  Bits { bit_len: usize, n: i64 },
+}
+
+fn unsupported_parameter(data_type: DataType, parameter: &Parameter) -> Error {
+  Error::UnsupportedMode(format!("Writing {data_type:?} with parameter {parameter:?} is not supported."))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::sim::{self, Simulator};
+
+  const fn command(addr: u16, block_len: usize) -> Command {
+    Command {
+      addr,
+      mode: AccessMode::ReadWrite,
+      read_function: Function::VirtualRead,
+      write_function: Function::VirtualWrite,
+      data_type: DataType::Int,
+      parameter: Parameter::Byte,
+      block_count: None,
+      block_len,
+      byte_len: block_len,
+      byte_pos: 0,
+      bit_len: None,
+      bit_pos: 0,
+      conversion: None,
+      lower_bound: None,
+      upper_bound: None,
+      stepping: None,
+      unit: None,
+      mapping: None,
+      default_value: None,
+      description: None,
+      priority: None,
+      filter_criterion: None,
+      url: None,
+    }
+  }
 
   #[test]
   fn parse_double_from_int() {
     let command = Command {
-      addr: 0x0886,
       mode: AccessMode::Read,
       data_type: DataType::Double,
       parameter: Parameter::Int,
-      block_count: None,
-      block_len: 4,
-      byte_len: 4,
-      byte_pos: 0,
-      bit_len: None,
-      bit_pos: 0,
       conversion: Some(Conversion::SecToHour),
-      lower_bound: None,
-      upper_bound: None,
       unit: Some("h"),
-      mapping: None,
+      ..command(0x0886, 4)
     };
     let value = command.parse_value(&[0x00, 0x95, 0xBA, 0x0A]).unwrap();
     assert_eq!(value, Value::Double(50000.0));
@@ -428,21 +807,10 @@ mod tests {
   #[test]
   fn parse_rotate_bytes_int() {
     let command = Command {
-      addr: 0x0D00,
       mode: AccessMode::Read,
-      data_type: DataType::Int,
       parameter: Parameter::Int,
-      block_count: None,
-      block_len: 2,
-      byte_len: 2,
-      byte_pos: 0,
-      bit_len: None,
-      bit_pos: 0,
       conversion: Some(Conversion::RotateBytes),
-      lower_bound: None,
-      upper_bound: None,
-      unit: None,
-      mapping: None,
+      ..command(0x0D00, 2)
     };
 
     let value = command.parse_value(&[0xb3, 0x04]).unwrap();
@@ -453,24 +821,189 @@ mod tests {
   #[test]
   fn parse_negative_s_int() {
     let command = Command {
-      addr: 21801,
       mode: AccessMode::Read,
       data_type: DataType::Double,
       parameter: Parameter::SInt,
-      block_count: None,
-      block_len: 2,
-      byte_len: 2,
-      byte_pos: 0,
-      bit_len: None,
-      bit_pos: 0,
       conversion: Some(Conversion::Div10),
       lower_bound: Some(-60.0),
       upper_bound: Some(60.0),
       unit: Some("°C"),
-      mapping: None,
+      ..command(21801, 2)
     };
 
     let value = command.parse_value(&[0xfd, 0xff]).unwrap();
     assert_eq!(value, Value::Double(-0.3));
   }
+
+  async fn connect(simulator: &Simulator) -> (Optolink, Protocol) {
+    let mut optolink = sim::connect(simulator.clone()).await;
+    let protocol = Protocol::detect(&mut optolink).await.unwrap();
+    (optolink, protocol)
+  }
+
+  #[tokio::test]
+  async fn set_converted() {
+    let simulator = Simulator::new();
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let command = Command {
+      data_type: DataType::ByteArray,
+      parameter: Parameter::String,
+      conversion: Some(Conversion::HexByteToAsciiByte),
+      ..command(0x7700, 7)
+    };
+
+    command.set(&mut optolink, protocol, Value::String("ABC".into())).await.unwrap();
+    let mut buf = [0; 7];
+    simulator.read(0x7700, &mut buf);
+    assert_eq!(&buf, b"ABC\0\0\0\0");
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), Value::String("ABC".into()));
+
+    command.set(&mut optolink, protocol, Value::String("A0 0".into())).await.unwrap();
+    simulator.read(0x7700, &mut buf);
+    assert_eq!(&buf, b"A0 0\0\0\0");
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), Value::String("A0 0".into()));
+
+    let res = command.set(&mut optolink, protocol, Value::String("ABCDEFGH".into())).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    let res = command.set(&mut optolink, protocol, Value::Int(1)).await;
+    assert!(matches!(res, Err(Error::UnsupportedConversion(_))));
+  }
+
+  #[tokio::test]
+  async fn set_default_value() {
+    let simulator = Simulator::new();
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let mut command = Command {
+      data_type: DataType::Double,
+      conversion: Some(Conversion::Div2),
+      lower_bound: Some(3.0),
+      upper_bound: Some(37.0),
+      unit: Some("°C"),
+      default_value: Some("20"),
+      ..command(0x2306, 1)
+    };
+
+    let default_value = command.default_value().unwrap();
+    assert_eq!(default_value, Value::Double(20.0));
+
+    command.set(&mut optolink, protocol, default_value).await.unwrap();
+    let mut buf = [0];
+    simulator.read(0x2306, &mut buf);
+    assert_eq!(buf, [40]);
+
+    command.data_type = DataType::Date;
+    command.default_value = Some("2024-02-29");
+    assert_eq!(command.default_value(), Some(Value::Date("2024-02-29".parse().unwrap())));
+
+    command.data_type = DataType::ByteArray;
+    command.default_value = Some("01 FF");
+    assert_eq!(command.default_value(), Some(Value::ByteArray(vec![0x01, 0xFF])));
+
+    command.data_type = DataType::Int;
+    command.default_value = Some("x");
+    assert_eq!(command.default_value(), None);
+  }
+
+  #[tokio::test]
+  async fn set_stepping() {
+    let simulator = Simulator::new();
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let command = Command {
+      data_type: DataType::Double,
+      conversion: Some(Conversion::Div2),
+      lower_bound: Some(3.0),
+      upper_bound: Some(37.0),
+      stepping: Some(0.5),
+      unit: Some("°C"),
+      ..command(0x2306, 1)
+    };
+
+    command.set(&mut optolink, protocol, Value::Double(21.5)).await.unwrap();
+    let mut buf = [0];
+    simulator.read(0x2306, &mut buf);
+    assert_eq!(buf, [43]);
+
+    match command.set(&mut optolink, protocol, Value::Double(21.3)).await {
+      Err(Error::InvalidArgument(message)) => {
+        assert!(message.contains("nearest valid values are 21 and 21.5"), "{message}")
+      },
+      res => panic!("expected invalid argument, got {res:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn set_bits() {
+    let simulator = Simulator::new();
+    simulator.write(0x2300, &[0b1010_0101, 0xFF]);
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let mut config = optolink.protocol_config();
+    config.verify_writes = true;
+    optolink.set_protocol_config(config);
+
+    let command = Command { byte_len: 1, bit_len: Some(3), bit_pos: 3, ..command(0x2300, 2) };
+
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), Value::Int(0b001));
+
+    command.set(&mut optolink, protocol, Value::Int(0b110)).await.unwrap();
+    let mut buf = [0; 2];
+    simulator.read(0x2300, &mut buf);
+    assert_eq!(buf, [0b1011_1001, 0xFF]);
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), Value::Int(0b110));
+
+    let res = command.set(&mut optolink, protocol, Value::Int(8)).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+  }
+
+  #[tokio::test]
+  async fn set_byte_pos() {
+    let simulator = Simulator::new();
+    simulator.write(0x2400, &[0x11, 0x22, 0x33, 0x44]);
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let command = Command { parameter: Parameter::Int, byte_len: 2, byte_pos: 1, ..command(0x2400, 4) };
+
+    command.set(&mut optolink, protocol, Value::Int(0xBBAA)).await.unwrap();
+    let mut buf = [0; 4];
+    simulator.read(0x2400, &mut buf);
+    assert_eq!(buf, [0x11, 0xAA, 0xBB, 0x44]);
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), Value::Int(0xBBAA));
+  }
+
+  #[tokio::test]
+  async fn set_array() {
+    let simulator = Simulator::new();
+    let (mut optolink, protocol) = connect(&simulator).await;
+
+    let command = Command {
+      data_type: DataType::Double,
+      parameter: Parameter::SInt,
+      block_count: Some(3),
+      conversion: Some(Conversion::Div10),
+      lower_bound: Some(-10.0),
+      upper_bound: Some(10.0),
+      ..command(0x2500, 6)
+    };
+
+    let values = Value::Array(vec![Value::Double(-1.5), Value::Double(0.0), Value::Double(2.3)]);
+    command.set(&mut optolink, protocol, values.clone()).await.unwrap();
+    let mut buf = [0; 6];
+    simulator.read(0x2500, &mut buf);
+    assert_eq!(buf, [0xF1, 0xFF, 0x00, 0x00, 0x17, 0x00]);
+    assert_eq!(command.get(&mut optolink, protocol).await.unwrap(), values);
+
+    let res = command.set(&mut optolink, protocol, Value::Array(vec![Value::Double(1.0)])).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    let values = Value::Array(vec![Value::Double(1.0), Value::Double(11.0), Value::Double(1.0)]);
+    let res = command.set(&mut optolink, protocol, values).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    let res = command.set(&mut optolink, protocol, Value::Double(1.0)).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+  }
 }
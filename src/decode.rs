@@ -0,0 +1,648 @@
+//! Offline decoding of captured Optolink traffic.
+//!
+//! A capture is split into `Chunk`s using `parse`, `parse_hex` or `parse_binary` and then decoded into `Frame`s using
+//! a `Decoder`. Each message is checked for a valid checksum, paired with its request or response and annotated with
+//! the values of all commands contained in its payload.
+//!
+//! # Examples
+//!
+//! ```
+//! use vcontrol::decode::{Decoder, parse_hex};
+//!
+//! let chunks = parse_hex("12:00:00.000 41 05 00 01 00 F8 08 06")?;
+//!
+//! for frame in Decoder::new().decode(&chunks) {
+//!   println!("{frame}");
+//! }
+//! # Ok::<(), vcontrol::Error>(())
+//! ```
+
+use std::fmt;
+
+use crate::{
+  Command, Device, Error, Function, MessageType, OutputValue, Protocol, Value,
+  commands::system_commands,
+  protocol::{
+    Hex,
+    vs1::{self, RESET, SYNC},
+    vs2::{self, ACK, LEADIN, NACK, START},
+  },
+};
+
+/// Bytes captured at once, e.g. a single line of a hex capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+  /// The time at which the bytes were captured, as written in the capture.
+  pub timestamp: Option<String>,
+  /// The captured bytes.
+  pub bytes: Vec<u8>,
+}
+
+/// Parses a capture in hex text format.
+///
+/// Each line contains hex bytes, e.g. `41 05 00 01 00 F8 08 06` or `4105000100F80806`, optionally preceded by a
+/// timestamp containing `:` or `.`, e.g. `12:00:00.123` or `0.012345 >` as written by a
+/// [`Recorder`](crate::record::Recorder). Timestamps without these, e.g. `1000`, cannot be told apart from hex
+/// bytes and are therefore not supported. Everything after `#` is ignored.
+pub fn parse_hex(s: &str) -> Result<Vec<Chunk>, Error> {
+  let mut chunks = Vec::new();
+
+  for (i, line) in s.lines().enumerate() {
+    let line = line.split('#').next().unwrap_or_default();
+    let mut tokens = line.split_whitespace().peekable();
+
+    let mut timestamp: Option<String> = None;
+    while let Some(token) = tokens.next_if(|token| is_timestamp(token) || !is_hex(token)) {
+      match &mut timestamp {
+        Some(timestamp) => {
+          timestamp.push(' ');
+          timestamp.push_str(token);
+        },
+        None => timestamp = Some(token.to_owned()),
+      }
+    }
+
+    if let Some(timestamp) = &timestamp
+      && !timestamp.split_whitespace().any(is_timestamp)
+    {
+      return Err(Error::InvalidFormat(format!("invalid timestamp “{timestamp}” in line {}", i + 1)));
+    }
+
+    let mut bytes = Vec::new();
+    for token in tokens {
+      if !is_hex(token) {
+        return Err(Error::InvalidFormat(format!("invalid hex bytes “{token}” in line {}", i + 1)));
+      }
+
+      bytes.extend((0..token.len()).step_by(2).map(|j| u8::from_str_radix(&token[j..(j + 2)], 16).unwrap()));
+    }
+
+    if !bytes.is_empty() {
+      chunks.push(Chunk { timestamp, bytes });
+    }
+  }
+
+  Ok(chunks)
+}
+
+fn is_hex(token: &str) -> bool {
+  token.len().is_multiple_of(2) && token.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn is_timestamp(token: &str) -> bool {
+  token.contains([':', '.'])
+}
+
+/// Parses a raw binary capture without timestamps.
+pub fn parse_binary(bytes: &[u8]) -> Vec<Chunk> {
+  vec![Chunk { timestamp: None, bytes: bytes.to_vec() }]
+}
+
+/// Parses a capture in hex text format if it is text, or as a raw binary capture otherwise.
+///
+/// A capture is considered text if it is valid UTF-8 without control characters other than whitespace.
+pub fn parse(bytes: &[u8]) -> Result<Vec<Chunk>, Error> {
+  if let Ok(s) = std::str::from_utf8(bytes)
+    && !s.chars().any(|c| c.is_control() && !c.is_whitespace())
+  {
+    return parse_hex(s);
+  }
+
+  Ok(parse_binary(bytes))
+}
+
+/// A frame decoded from a capture.
+#[derive(Debug)]
+pub struct Frame {
+  /// The timestamp of the chunk in which the frame starts.
+  pub timestamp: Option<String>,
+  /// The raw bytes of the frame.
+  pub bytes: Vec<u8>,
+  /// The kind of the frame.
+  pub kind: FrameKind,
+}
+
+/// The kind of a `Frame`.
+#[derive(Debug)]
+pub enum FrameKind {
+  /// Resets the controller to `Vs1` (`0x04`).
+  Reset,
+  /// Sent periodically by an idle controller (`0x05`).
+  Sync,
+  /// Starts a `Vs2` session (`0x16 0x00 0x00`).
+  Start,
+  /// Acknowledges a `Vs2` telegram (`0x06`).
+  Ack,
+  /// Rejects a `Vs2` telegram (`0x15`).
+  Nack,
+  /// A request or response.
+  Message(Message),
+  /// Bytes which could not be decoded.
+  Unknown,
+}
+
+/// A request or response decoded from a capture.
+#[derive(Debug)]
+pub struct Message {
+  /// The protocol of the message.
+  pub protocol: Protocol,
+  /// The message type, `Vs1` responses are either `Response` or, for failed writes, `Error`.
+  pub message_type: MessageType,
+  /// The function, `Vs1` functions are mapped to their `Vs2` equivalent.
+  pub function: Function,
+  /// The address.
+  pub addr: u16,
+  /// The length of the value.
+  pub len: usize,
+  /// The value sent with a write request or returned by a read response.
+  pub payload: Vec<u8>,
+  /// Whether the checksum is valid, or `None` for `Vs1`, which has no checksum.
+  pub checksum_valid: Option<bool>,
+  /// The index of the frame containing the corresponding request or response.
+  pub pair: Option<usize>,
+  /// The commands contained in the payload and their decoded values.
+  pub values: Vec<(&'static str, Result<OutputValue, Error>)>,
+}
+
+impl Message {
+  /// Returns whether the payload contains a value, i.e. for write requests and read responses.
+  fn carries_value(&self) -> bool {
+    match self.message_type {
+      MessageType::Request => !self.function.returns_payload(),
+      MessageType::Response => self.function.returns_payload(),
+      _ => false,
+    }
+  }
+}
+
+impl fmt::Display for Frame {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(timestamp) = &self.timestamp {
+      write!(f, "{timestamp} ")?;
+    }
+
+    match &self.kind {
+      FrameKind::Reset => write!(f, "reset")?,
+      FrameKind::Sync => write!(f, "sync")?,
+      FrameKind::Start => write!(f, "start")?,
+      FrameKind::Ack => write!(f, "ack")?,
+      FrameKind::Nack => write!(f, "nack")?,
+      FrameKind::Unknown => write!(f, "unknown")?,
+      FrameKind::Message(message) => {
+        write!(
+          f,
+          "{} {} {:?} 0x{:04X} ({} bytes)",
+          message.protocol, message.message_type, message.function, message.addr, message.len
+        )?;
+
+        if message.checksum_valid == Some(false) {
+          write!(f, ", invalid checksum")?;
+        }
+
+        if message.pair.is_none() {
+          match message.message_type {
+            MessageType::Request => write!(f, ", unanswered")?,
+            _ => write!(f, ", unrequested")?,
+          }
+        }
+      },
+    }
+
+    write!(f, ": {}", Hex(&self.bytes))?;
+
+    if let FrameKind::Message(message) = &self.kind {
+      for (name, value) in &message.values {
+        match value {
+          Ok(value) => write!(f, "\n  {name} = {value}")?,
+          Err(err) => write!(f, "\n  {name}: {err}")?,
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Decodes captured traffic into `Frame`s.
+///
+/// Decoding starts in `Vs1` mode and switches to `Vs2` mode on `Start` or on the first `Vs2` telegram with a valid
+/// checksum. Values are annotated using the system commands and, once known, the commands of the device. Unless set
+/// explicitly, the device is detected from the first device identifier read in the capture.
+#[derive(Debug, Default)]
+pub struct Decoder {
+  device: Option<&'static Device>,
+}
+
+impl Decoder {
+  /// Creates a new `Decoder` which detects the device from the capture.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the device used for annotating values.
+  pub fn device(mut self, device: &'static Device) -> Self {
+    self.device = Some(device);
+    self
+  }
+
+  /// Decodes the given chunks into frames.
+  pub fn decode(&self, chunks: &[Chunk]) -> Vec<Frame> {
+    let mut stream = Vec::new();
+    let mut starts = Vec::new();
+    for chunk in chunks {
+      starts.push(stream.len());
+      stream.extend(&chunk.bytes);
+    }
+
+    let timestamp_at = |pos: usize| {
+      let i = starts.partition_point(|&start| start <= pos);
+      i.checked_sub(1).and_then(|i| chunks[i].timestamp.clone())
+    };
+
+    let mut session =
+      Session { device: self.device, protocol: Protocol::Vs1, frames: Vec::new(), pending: None, vs1_request: None };
+
+    let mut pos = 0;
+    while pos < stream.len() {
+      let (len, kind) = session.next(&stream[pos..]);
+      session.push(timestamp_at(pos), stream[pos..(pos + len)].to_vec(), kind);
+      pos += len;
+    }
+
+    session.frames
+  }
+}
+
+#[derive(Debug)]
+struct Session {
+  device: Option<&'static Device>,
+  protocol: Protocol,
+  frames: Vec<Frame>,
+  /// The index of the last unanswered request.
+  pending: Option<usize>,
+  /// The function, address and length of the `Vs1` request whose response follows.
+  vs1_request: Option<(Function, u16, usize)>,
+}
+
+impl Session {
+  /// Returns the length and kind of the frame at the start of `rest`.
+  fn next(&mut self, rest: &[u8]) -> (usize, FrameKind) {
+    if let Some((function, addr, len)) = self.vs1_request.take() {
+      return Self::vs1_response(rest, function, addr, len);
+    }
+
+    match rest[0] {
+      RESET => {
+        self.protocol = Protocol::Vs1;
+        (1, FrameKind::Reset)
+      },
+      SYNC => (1, FrameKind::Sync),
+      _ if rest.starts_with(&START) => {
+        self.protocol = Protocol::Vs2;
+        (START.len(), FrameKind::Start)
+      },
+      LEADIN => match Self::vs2_telegram(rest) {
+        Some((len, message)) if self.protocol == Protocol::Vs2 || message.checksum_valid == Some(true) => {
+          self.protocol = Protocol::Vs2;
+          (len, FrameKind::Message(message))
+        },
+        _ => (1, FrameKind::Unknown),
+      },
+      ACK if self.protocol == Protocol::Vs2 => (1, FrameKind::Ack),
+      NACK if self.protocol == Protocol::Vs2 => (1, FrameKind::Nack),
+      0x01 if self.protocol == Protocol::Vs1 => self.vs1_request(rest),
+      _ => (1, FrameKind::Unknown),
+    }
+  }
+
+  fn vs2_telegram(rest: &[u8]) -> Option<(usize, Message)> {
+    let message_len = usize::from(*rest.get(1)?);
+    if message_len < 5 {
+      return None;
+    }
+
+    let end = 1 + 1 + message_len + 1;
+    let telegram = rest.get(..end)?;
+
+    let message_type = MessageType::try_from(telegram[2]).ok()?;
+    let function = Function::try_from(telegram[3]).ok()?;
+    let checksum_valid = vs2::wrapping_sum(&telegram[1..(end - 1)]) == telegram[end - 1];
+
+    let message = Message {
+      protocol: Protocol::Vs2,
+      message_type,
+      function,
+      addr: u16::from_be_bytes([telegram[4], telegram[5]]),
+      len: usize::from(telegram[6]),
+      payload: telegram[7..(end - 1)].to_vec(),
+      checksum_valid: Some(checksum_valid),
+      pair: None,
+      values: Vec::new(),
+    };
+
+    Some((end, message))
+  }
+
+  fn vs1_request(&mut self, rest: &[u8]) -> (usize, FrameKind) {
+    let Some(&[_, function, addr_hi, addr_lo, len]) = rest.get(..5) else {
+      return (1, FrameKind::Unknown);
+    };
+    let Ok(function) = vs1::Function::try_from(function) else {
+      return (1, FrameKind::Unknown);
+    };
+
+    let function = function.to_vs2();
+    let addr = u16::from_be_bytes([addr_hi, addr_lo]);
+    let len = usize::from(len);
+
+    let (frame_len, payload) = if function.sends_payload() {
+      match rest.get(5..(5 + len)) {
+        Some(payload) => (5 + len, payload.to_vec()),
+        None => return (1, FrameKind::Unknown),
+      }
+    } else {
+      (5, Vec::new())
+    };
+
+    self.vs1_request = Some((function, addr, len));
+
+    let message = Message {
+      protocol: Protocol::Vs1,
+      message_type: MessageType::Request,
+      function,
+      addr,
+      len,
+      payload,
+      checksum_valid: None,
+      pair: None,
+      values: Vec::new(),
+    };
+
+    (frame_len, FrameKind::Message(message))
+  }
+
+  fn vs1_response(rest: &[u8], function: Function, addr: u16, len: usize) -> (usize, FrameKind) {
+    let (frame_len, message_type, payload) = if function.returns_payload() {
+      match rest.get(..len) {
+        Some(payload) => (len, MessageType::Response, payload.to_vec()),
+        None => return (rest.len(), FrameKind::Unknown),
+      }
+    } else if rest[0] == 0x00 {
+      (1, MessageType::Response, Vec::new())
+    } else {
+      (1, MessageType::Error, Vec::new())
+    };
+
+    let message = Message {
+      protocol: Protocol::Vs1,
+      message_type,
+      function,
+      addr,
+      len,
+      payload,
+      checksum_valid: None,
+      pair: None,
+      values: Vec::new(),
+    };
+
+    (frame_len, FrameKind::Message(message))
+  }
+
+  fn push(&mut self, timestamp: Option<String>, bytes: Vec<u8>, mut kind: FrameKind) {
+    let index = self.frames.len();
+
+    match &mut kind {
+      FrameKind::Unknown => {
+        if let Some(Frame { kind: FrameKind::Unknown, bytes: unknown, .. }) = self.frames.last_mut() {
+          unknown.extend(bytes);
+          return;
+        }
+      },
+      FrameKind::Message(message) => {
+        if message.message_type == MessageType::Request {
+          self.pending = Some(index);
+        } else if let Some(request) = self.pending
+          && let FrameKind::Message(request_message) = &mut self.frames[request].kind
+          && request_message.function == message.function
+          && request_message.addr == message.addr
+        {
+          request_message.pair = Some(index);
+          message.pair = Some(request);
+          self.pending = None;
+        }
+
+        if message.carries_value() && message.checksum_valid != Some(false) && !message.payload.is_empty() {
+          message.values = self.annotate(message);
+          self.detect_device(message);
+        }
+      },
+      _ => (),
+    }
+
+    self.frames.push(Frame { timestamp, bytes, kind });
+  }
+
+  /// Decodes the values of all known commands which are completely contained in the payload of `message`.
+  fn annotate(&self, message: &Message) -> Vec<(&'static str, Result<OutputValue, Error>)> {
+    let start = usize::from(message.addr);
+    let end = start + message.payload.len();
+    let is_write = message.message_type == MessageType::Request;
+
+    let device_commands = self.device.into_iter().flat_map(|device| device.commands().entries());
+
+    let mut commands = system_commands()
+      .entries()
+      .chain(device_commands)
+      .map(|(&name, &command)| (name, command))
+      .filter(|(_, command)| {
+        if is_write {
+          command.access_mode().is_write() && command.write_function() == message.function
+        } else {
+          command.access_mode().is_read() && command.read_function() == message.function
+        }
+      })
+      .filter(|(_, command)| {
+        let addr = usize::from(command.addr());
+        command.block_len() > 0 && addr >= start && addr + command.block_len() <= end
+      })
+      .collect::<Vec<(&'static str, &'static Command)>>();
+    commands.sort_by_key(|&(name, command)| (command.addr(), name));
+
+    commands
+      .into_iter()
+      .map(|(name, command)| {
+        let offset = usize::from(command.addr()) - start;
+        let block = &message.payload[offset..(offset + command.block_len())];
+        let value =
+          command.deserialize_block(block).map(|value| OutputValue::from_command(command, self.device, value));
+
+        (name, value)
+      })
+      .collect()
+  }
+
+  fn detect_device(&mut self, message: &Message) {
+    if self.device.is_some() {
+      return;
+    }
+
+    for (_, value) in &message.values {
+      if let Ok(OutputValue { value: Value::DeviceId(device_id), .. }) = value {
+        self.device = Device::detect(*device_id, None);
+
+        if let Some(device) = self.device {
+          log::debug!("Detected device '{}'.", device.name());
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{sim::vscot_ho1, types::DeviceId};
+
+  fn message(frame: &Frame) -> &Message {
+    match &frame.kind {
+      FrameKind::Message(message) => message,
+      kind => panic!("expected message, got {kind:?}"),
+    }
+  }
+
+  #[test]
+  fn parse_hex_capture() {
+    let chunks = parse_hex(
+      "
+      # Vitosoft session
+      12:00:00.000 04
+      12:00:00.010 16 00 00 # start
+      4105000100F80806
+      0.000120 > 05
+      ",
+    )
+    .unwrap();
+
+    assert_eq!(
+      chunks,
+      vec![
+        Chunk { timestamp: Some("12:00:00.000".into()), bytes: vec![0x04] },
+        Chunk { timestamp: Some("12:00:00.010".into()), bytes: vec![0x16, 0x00, 0x00] },
+        Chunk { timestamp: None, bytes: vec![0x41, 0x05, 0x00, 0x01, 0x00, 0xF8, 0x08, 0x06] },
+        Chunk { timestamp: Some("0.000120 >".into()), bytes: vec![0x05] },
+      ]
+    );
+
+    assert!(parse_hex("12:00 04 0").is_err());
+    assert!(parse_hex("12:00 04 XY").is_err());
+
+    // Timestamps need a `:` or `.`, otherwise they are either hex bytes or invalid.
+    assert_eq!(
+      parse_hex("2024-01-01 12:00:00 04").unwrap(),
+      vec![Chunk { timestamp: Some("2024-01-01 12:00:00".into()), bytes: vec![0x04] }]
+    );
+    assert_eq!(parse_hex("1000 04").unwrap(), vec![Chunk { timestamp: None, bytes: vec![0x10, 0x00, 0x04] }]);
+    assert!(parse_hex("100 04").is_err());
+    assert!(parse_hex("> 04").is_err());
+
+    assert_eq!(parse(&[0x04, 0xFF]).unwrap(), vec![Chunk { timestamp: None, bytes: vec![0x04, 0xFF] }]);
+    assert_eq!(parse(&[0x05, 0x05]).unwrap(), vec![Chunk { timestamp: None, bytes: vec![0x05, 0x05] }]);
+    assert!(matches!(parse(b"12:00 04 XY"), Err(Error::InvalidFormat(_))));
+  }
+
+  #[test]
+  fn decode_vs2() {
+    let chunks = parse_hex(
+      "
+      0.000 04
+      0.010 05
+      0.020 16 00 00
+      0.030 06
+      0.100 41 05 00 01 00 F8 08 06
+      0.110 06
+      0.150 41 0D 01 01 00 F8 08 20 CB 00 08 00 00 01 46 49
+      0.200 41 05 00 01 55 25 02 82
+      0.210 06
+      0.250 41 07 01 01 55 25 02 DD FF 61
+      0.300 41 05 00 01 55 25 02 00
+      ",
+    )
+    .unwrap();
+
+    let frames = Decoder::new().decode(&chunks);
+    assert_eq!(frames.len(), 11);
+
+    assert!(matches!(frames[0].kind, FrameKind::Reset));
+    assert!(matches!(frames[1].kind, FrameKind::Sync));
+    assert!(matches!(frames[2].kind, FrameKind::Start));
+    assert!(matches!(frames[3].kind, FrameKind::Ack));
+    assert_eq!(frames[3].timestamp.as_deref(), Some("0.030"));
+
+    let request = message(&frames[4]);
+    assert_eq!(request.protocol, Protocol::Vs2);
+    assert_eq!(request.message_type, MessageType::Request);
+    assert_eq!(request.function, Function::VirtualRead);
+    assert_eq!(request.addr, 0x00F8);
+    assert_eq!(request.len, 8);
+    assert_eq!(request.checksum_valid, Some(true));
+    assert_eq!(request.pair, Some(6));
+    assert!(request.values.is_empty());
+
+    let response = message(&frames[6]);
+    assert_eq!(response.message_type, MessageType::Response);
+    assert_eq!(response.pair, Some(4));
+    let device_id = DeviceId::from_bytes(&[0x20, 0xCB, 0x00, 0x08, 0x00, 0x00, 0x01, 0x46]);
+    assert!(
+      matches!(&response.values[..], [("device_id", Ok(OutputValue { value: Value::DeviceId(id), .. }))] if *id == device_id)
+    );
+
+    let response = message(&frames[9]);
+    assert_eq!(response.pair, Some(7));
+    let (_, value) = response.values.iter().find(|(name, _)| *name == "TiefpassTemperaturwert_ATS").unwrap();
+    assert_eq!(value.as_ref().unwrap().value, Value::Double(-3.5));
+    assert!(frames[9].to_string().contains("TiefpassTemperaturwert_ATS = -3.5"));
+
+    let request = message(&frames[10]);
+    assert_eq!(request.checksum_valid, Some(false));
+    assert_eq!(request.pair, None);
+    assert!(frames[10].to_string().ends_with("invalid checksum, unanswered: 41 05 00 01 55 25 02 00"));
+  }
+
+  #[test]
+  fn decode_vs1() {
+    let decoder = Decoder::new().device(vscot_ho1());
+
+    let frames = decoder.decode(&parse_binary(&[
+      0xFF, 0xFE, // Garbage.
+      0x04, 0x05, // Reset and sync.
+      0x01, 0xF7, 0x55, 0x25, 0x02, 0xDD, 0xFF, // Read.
+      0x01, 0xF4, 0x23, 0x23, 0x01, 0x02, 0x00, // Write.
+    ]));
+    assert_eq!(frames.len(), 7);
+
+    assert!(matches!(frames[0].kind, FrameKind::Unknown));
+    assert_eq!(frames[0].bytes, [0xFF, 0xFE]);
+    assert!(matches!(frames[1].kind, FrameKind::Reset));
+    assert!(matches!(frames[2].kind, FrameKind::Sync));
+
+    let request = message(&frames[3]);
+    assert_eq!(request.protocol, Protocol::Vs1);
+    assert_eq!(request.function, Function::VirtualRead);
+    assert_eq!(request.checksum_valid, None);
+    assert_eq!(request.pair, Some(4));
+
+    let response = message(&frames[4]);
+    assert_eq!(response.payload, [0xDD, 0xFF]);
+    let (_, value) = response.values.iter().find(|(name, _)| *name == "TiefpassTemperaturwert_ATS").unwrap();
+    assert_eq!(value.as_ref().unwrap().value, Value::Double(-3.5));
+
+    let request = message(&frames[5]);
+    assert_eq!(request.function, Function::VirtualWrite);
+    assert_eq!(request.addr, 0x2323);
+    assert_eq!(request.payload, [0x02]);
+
+    let response = message(&frames[6]);
+    assert_eq!(response.message_type, MessageType::Response);
+    assert_eq!(response.pair, Some(5));
+  }
+}
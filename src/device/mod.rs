@@ -1,7 +1,7 @@
 use phf;
 
 use crate::{
-  Command,
+  Command, Locale, Mapping,
   types::{DeviceId, DeviceIdF0},
 };
 
@@ -24,7 +24,7 @@ pub use self::codegen::*;
 pub struct Device {
   name: &'static str,
   commands: &'static phf::Map<&'static str, &'static Command>,
-  errors: &'static phf::Map<i32, &'static str>,
+  errors: &'static Mapping,
 }
 
 impl Device {
@@ -43,11 +43,21 @@ impl Device {
     self.commands.get(name.as_ref()).copied()
   }
 
-  /// Get mapping from error codes to strings.
-  pub fn errors(&self) -> &'static phf::Map<i32, &'static str> {
+  /// Get mapping from error codes to strings in the given locale.
+  pub fn errors(&self, locale: Locale) -> &'static phf::Map<i32, &'static str> {
+    self.errors.labels(locale)
+  }
+
+  /// Get mapping from error codes to strings in all locales.
+  pub(crate) fn error_mapping(&self) -> &'static Mapping {
     self.errors
   }
 
+  /// Find a device by name.
+  pub fn by_name(name: &str) -> Option<&'static Self> {
+    DEVICES.values().copied().find(|device| device.name() == name)
+  }
+
   /// Detect a device by identifier.
   pub fn detect(device_id: DeviceId, device_id_f0: Option<DeviceIdF0>) -> Option<&'static Self> {
     let devices = DEVICES.entries().filter(|(device_id_range, _)| device_id.id == device_id_range.id);
@@ -1,22 +1,38 @@
 use std::{error::Error as StdError, fmt, io, string::FromUtf8Error};
 
-use crate::types::{DeviceId, DeviceIdF0};
+use crate::{
+  ProtocolError,
+  types::{DeviceId, DeviceIdF0},
+};
 
 #[derive(Debug)]
 pub enum Error {
   UnsupportedDevice(DeviceId, Option<DeviceIdF0>),
   UnsupportedCommand(String),
   UnsupportedMode(String),
+  UnsupportedConversion(String),
   InvalidArgument(String),
   InvalidFormat(String),
+  VerificationFailed(String),
   UnknownEnumVariant(String),
   Utf8(FromUtf8Error),
+  Timeout(String),
+  Protocol(ProtocolError),
   Io(io::Error),
 }
 
 impl From<io::Error> for Error {
   fn from(err: io::Error) -> Error {
-    Error::Io(err)
+    match err.kind() {
+      io::ErrorKind::TimedOut => Error::Timeout(err.to_string()),
+      io::ErrorKind::InvalidData if err.get_ref().is_some_and(|err| err.is::<ProtocolError>()) => {
+        match err.into_inner().map(|err| err.downcast::<ProtocolError>()) {
+          Some(Ok(err)) => Error::Protocol(*err),
+          _ => unreachable!(),
+        }
+      },
+      _ => Error::Io(err),
+    }
   }
 }
 
@@ -38,10 +54,14 @@ impl fmt::Display for Error {
       },
       Error::UnsupportedCommand(command) => write!(f, "command {} is not supported", command),
       Error::UnsupportedMode(description) => description.fmt(f),
+      Error::UnsupportedConversion(description) => description.fmt(f),
       Error::InvalidArgument(description) => description.fmt(f),
       Error::InvalidFormat(description) => description.fmt(f),
+      Error::VerificationFailed(description) => description.fmt(f),
       Error::UnknownEnumVariant(description) => description.fmt(f),
       Error::Utf8(err) => err.fmt(f),
+      Error::Timeout(description) => description.fmt(f),
+      Error::Protocol(err) => err.fmt(f),
       Error::Io(err) => err.fmt(f),
     }
   }
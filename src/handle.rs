@@ -0,0 +1,197 @@
+use std::io;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{Device, Error, OutputValue, Protocol, VControl, Value};
+
+/// The priority of a request sent via a `VControlHandle`.
+///
+/// Pending requests with a higher priority are always executed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Priority {
+  /// For background tasks, e.g. periodic polling.
+  Low,
+  /// For regular reads.
+  #[default]
+  Normal,
+  /// For writes.
+  High,
+}
+
+#[derive(Debug)]
+enum Request {
+  Get { command: String, reply: oneshot::Sender<Result<OutputValue, Error>> },
+  GetMany { commands: Vec<String>, reply: oneshot::Sender<Result<Vec<Result<OutputValue, Error>>, Error>> },
+  Set { command: String, input: Value, reply: oneshot::Sender<Result<(), Error>> },
+}
+
+impl Request {
+  async fn execute(self, vcontrol: &mut VControl) {
+    // Sending a reply only fails if the caller is no longer waiting for it.
+    match self {
+      Self::Get { command, reply } => {
+        let _ = reply.send(vcontrol.get(&command).await);
+      },
+      Self::GetMany { commands, reply } => {
+        let commands = commands.iter().map(String::as_str).collect::<Vec<_>>();
+        let _ = reply.send(vcontrol.get_many(&commands).await);
+      },
+      Self::Set { command, input, reply } => {
+        let _ = reply.send(vcontrol.set(&command, input).await);
+      },
+    }
+  }
+}
+
+/// A cloneable handle to a `VControl` running in a background task.
+///
+/// Requests from all clones are queued and executed one at a time, ordered by their `Priority`.
+/// Writes are always executed with `Priority::High`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use vcontrol::{Optolink, Priority, VControl, VControlHandle};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let vcontrol = VControl::connect(Optolink::open("/dev/ttyUSB0").await?).await?;
+/// let handle = VControlHandle::spawn(vcontrol);
+///
+/// let background = handle.clone().read_priority(Priority::Low);
+/// tokio::spawn(async move { background.get("Gemischte_AT").await });
+///
+/// let value = handle.get("Gemischte_AT").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VControlHandle {
+  high: mpsc::UnboundedSender<Request>,
+  normal: mpsc::UnboundedSender<Request>,
+  low: mpsc::UnboundedSender<Request>,
+  device: &'static Device,
+  protocol: Protocol,
+  read_priority: Priority,
+}
+
+impl VControlHandle {
+  /// Moves the given `VControl` into a background task and returns a handle to it.
+  ///
+  /// The task stops once all handles are dropped.
+  pub fn spawn(mut vcontrol: VControl) -> Self {
+    let (high, mut high_rx) = mpsc::unbounded_channel();
+    let (normal, mut normal_rx) = mpsc::unbounded_channel();
+    let (low, mut low_rx) = mpsc::unbounded_channel();
+
+    let device = vcontrol.device();
+    let protocol = vcontrol.protocol();
+
+    tokio::spawn(async move {
+      loop {
+        let request: Request = tokio::select! {
+          biased;
+          Some(request) = high_rx.recv() => request,
+          Some(request) = normal_rx.recv() => request,
+          Some(request) = low_rx.recv() => request,
+          else => break,
+        };
+
+        request.execute(&mut vcontrol).await;
+      }
+
+      log::trace!("VControlHandle: all handles dropped, stopping.");
+    });
+
+    Self { high, normal, low, device, protocol, read_priority: Priority::Normal }
+  }
+
+  /// Sets the priority used for reads sent via this handle.
+  pub fn read_priority(mut self, priority: Priority) -> Self {
+    self.read_priority = priority;
+    self
+  }
+
+  pub fn device(&self) -> &'static Device {
+    self.device
+  }
+
+  pub fn protocol(&self) -> Protocol {
+    self.protocol
+  }
+
+  async fn request<T>(
+    &self,
+    priority: Priority,
+    request: impl FnOnce(oneshot::Sender<Result<T, Error>>) -> Request,
+  ) -> Result<T, Error> {
+    let sender = match priority {
+      Priority::High => &self.high,
+      Priority::Normal => &self.normal,
+      Priority::Low => &self.low,
+    };
+
+    let (reply, response) = oneshot::channel();
+    sender.send(request(reply)).map_err(|_| stopped())?;
+    response.await.map_err(|_| stopped())?
+  }
+
+  /// Gets the value for the given command.
+  pub async fn get(&self, command: &str) -> Result<OutputValue, Error> {
+    self.request(self.read_priority, |reply| Request::Get { command: command.to_owned(), reply }).await
+  }
+
+  /// Gets the values for the given commands, see `VControl::get_many`.
+  pub async fn get_many(&self, commands: &[&str]) -> Result<Vec<Result<OutputValue, Error>>, Error> {
+    let commands = commands.iter().map(|&command| command.to_owned()).collect();
+    self.request(self.read_priority, |reply| Request::GetMany { commands, reply }).await
+  }
+
+  /// Sets the value for the given command.
+  pub async fn set(&self, command: &str, input: Value) -> Result<(), Error> {
+    self.request(Priority::High, |reply| Request::Set { command: command.to_owned(), input, reply }).await
+  }
+
+  /// Resets the given command to its factory default value, see `VControl::reset_to_default`.
+  pub async fn reset_to_default(&self, command: &str) -> Result<(), Error> {
+    let default_value = crate::commands::system_command(command)
+      .or_else(|| self.device.command(command))
+      .ok_or_else(|| Error::UnsupportedCommand(command.to_owned()))?
+      .default_value()
+      .ok_or_else(|| Error::InvalidArgument(format!("{command} has no default value.")))?;
+
+    self.set(command, default_value).await
+  }
+}
+
+fn stopped() -> Error {
+  Error::Io(io::Error::new(io::ErrorKind::BrokenPipe, "VControl task stopped"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sim::{Simulator, connect, vscot_ho1};
+
+  async fn spawn() -> VControlHandle {
+    let simulator = Simulator::from_device(vscot_ho1());
+    simulator.write(0x27D3, &[10]);
+
+    VControlHandle::spawn(VControl::connect(connect(simulator).await).await.unwrap())
+  }
+
+  #[tokio::test]
+  async fn writes_first() {
+    let handle = spawn().await;
+    let background = handle.clone().read_priority(Priority::Low);
+
+    // Both requests are queued before the background task runs, so the write is executed first.
+    let (value, res) =
+      tokio::join!(background.get("KD3_KonfiNeigungA1"), handle.set("KD3_KonfiNeigungA1", Value::Double(1.5)));
+    res.unwrap();
+    assert_eq!(value.unwrap().value, Value::Double(1.5));
+
+    let values = handle.get_many(&["KD3_KonfiNeigungA1", "Unbekannt"]).await.unwrap();
+    assert_eq!(values[0].as_ref().unwrap().value, Value::Double(1.5));
+    assert!(matches!(values[1], Err(Error::UnsupportedCommand(_))));
+  }
+}
@@ -10,16 +10,22 @@ pub use crate::command::Command;
 
 pub(crate) mod mappings;
 
+mod mapping;
+pub use crate::mapping::Mapping;
+
+mod locale;
+pub use crate::locale::{Locale, Translation};
+
 pub mod commands;
 
 mod access_mode;
 pub use crate::access_mode::AccessMode;
 
 mod optolink;
-pub use crate::optolink::Optolink;
+pub use crate::optolink::{Optolink, Transport};
 
 mod protocol;
-pub use crate::protocol::Protocol;
+pub use crate::protocol::{Function, MessageType, Protocol, ProtocolConfig, ProtocolError};
 
 pub mod device;
 pub use crate::device::Device;
@@ -27,6 +33,12 @@ pub use crate::device::Device;
 mod vcontrol;
 pub use crate::vcontrol::*;
 
+mod poller;
+pub use crate::poller::{Poller, Update};
+
+mod handle;
+pub use crate::handle::{Priority, VControlHandle};
+
 mod value;
 pub use crate::value::{OutputValue, Value};
 
@@ -37,3 +49,13 @@ mod parameter;
 pub use crate::parameter::Parameter;
 
 mod conversion;
+
+pub mod decode;
+
+pub mod record;
+
+#[cfg(feature = "typed")]
+pub mod typed;
+
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
@@ -0,0 +1,101 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::Error;
+
+/// A language in which labels of mappings and errors are available.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+  /// English.
+  #[default]
+  En,
+  /// German.
+  De,
+}
+
+impl Locale {
+  /// All available locales.
+  pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+  /// Returns the language code, e.g. `en`.
+  pub fn code(self) -> &'static str {
+    match self {
+      Locale::En => "en",
+      Locale::De => "de",
+    }
+  }
+}
+
+impl fmt::Display for Locale {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.code().fmt(f)
+  }
+}
+
+impl FromStr for Locale {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::ALL
+      .into_iter()
+      .find(|locale| locale.code().eq_ignore_ascii_case(s))
+      .ok_or_else(|| Error::InvalidArgument(format!("unknown locale “{s}”")))
+  }
+}
+
+/// A text which is available in every `Locale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Translation {
+  pub(crate) en: &'static str,
+  pub(crate) de: &'static str,
+}
+
+impl Translation {
+  /// Returns the text in the given locale.
+  pub fn get(&self, locale: Locale) -> &'static str {
+    match locale {
+      Locale::En => self.en,
+      Locale::De => self.de,
+    }
+  }
+}
+
+impl fmt::Display for Translation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.get(Locale::default()).fmt(f)
+  }
+}
+
+/// Serializes the text in the default locale.
+impl Serialize for Translation {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.get(Locale::default()).serialize(serializer)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_locale() {
+    for locale in Locale::ALL {
+      assert_eq!(locale.to_string().parse::<Locale>().unwrap(), locale);
+    }
+
+    assert_eq!("DE".parse::<Locale>().unwrap(), Locale::De);
+    assert!("fr".parse::<Locale>().is_err());
+  }
+
+  #[test]
+  #[ignore = "`build/translations.used.json` contains no German texts until it is regenerated"]
+  fn german_mapping_labels() {
+    let device = crate::Device::by_name("VScotHO1_4").unwrap();
+    let mapping = device.commands()["HK_AktuelleBetriebsartA1M1"].mapping().unwrap();
+
+    assert_eq!(mapping.get(2, Locale::En), Some("Standard mode"));
+    assert_ne!(mapping.get(2, Locale::De), mapping.get(2, Locale::En));
+  }
+}
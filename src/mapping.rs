@@ -0,0 +1,32 @@
+use serde::{Serialize, Serializer};
+
+use crate::Locale;
+
+/// A mapping from values to labels, available in every `Locale`.
+#[derive(Debug, PartialEq)]
+pub struct Mapping {
+  pub(crate) en: phf::Map<i32, &'static str>,
+  pub(crate) de: phf::Map<i32, &'static str>,
+}
+
+impl Mapping {
+  /// Returns the labels in the given locale.
+  pub fn labels(&self, locale: Locale) -> &phf::Map<i32, &'static str> {
+    match locale {
+      Locale::En => &self.en,
+      Locale::De => &self.de,
+    }
+  }
+
+  /// Returns the label for the given value in the given locale.
+  pub fn get(&self, key: i32, locale: Locale) -> Option<&'static str> {
+    self.labels(locale).get(&key).copied()
+  }
+}
+
+/// Serializes the labels in the default locale.
+impl Serialize for Mapping {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.labels(Locale::default()).serialize(serializer)
+  }
+}
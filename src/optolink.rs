@@ -1,6 +1,7 @@
 use core::{
+  future::Future,
   pin::Pin,
-  task::{Context, Poll},
+  task::{Context, Poll, Waker},
 };
 use std::{
   fmt, io,
@@ -15,10 +16,75 @@ use tokio::{
 };
 use tokio_serial::{DataBits, FlowControl, Parity, SerialPortBuilderExt, SerialStream, StopBits};
 
+use crate::{ProtocolConfig, record::Recorder};
+
+/// A transport for an Optolink connection.
+///
+/// Implement this trait to use a custom stream with special handling for purging
+/// or re-initializing the connection, see [`Optolink::from_transport`].
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {
+  /// Purge all contents of the input buffer.
+  ///
+  /// By default, all bytes which are immediately available are read and discarded.
+  fn purge(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+    Box::pin(async move { discard_available(self) })
+  }
+
+  /// Re-initialize the connection after an error.
+  ///
+  /// By default, this does nothing.
+  fn reinitialize(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+    Box::pin(async { Ok(()) })
+  }
+}
+
+/// Read and discard all bytes which can be read without waiting.
+fn discard_available<S: AsyncRead + Unpin + ?Sized>(stream: &mut S) -> io::Result<()> {
+  let mut cx = Context::from_waker(Waker::noop());
+  let mut buf = [0; 16];
+
+  loop {
+    let mut read_buf = ReadBuf::new(&mut buf);
+
+    match Pin::new(&mut *stream).poll_read(&mut cx, &mut read_buf) {
+      Poll::Ready(Ok(())) if read_buf.filled().is_empty() => return Ok(()),
+      Poll::Ready(Ok(())) => continue,
+      Poll::Ready(Err(err)) => return Err(err),
+      Poll::Pending => return Ok(()),
+    }
+  }
+}
+
+/// A generic stream using the default `Transport` behaviour.
+struct Stream<S>(S);
+
+impl<S: AsyncRead + Unpin> AsyncRead for Stream<S> {
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_read(cx, buf)
+  }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Stream<S> {
+  fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    Pin::new(&mut self.0).poll_write(cx, buf)
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_flush(cx)
+  }
+
+  fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_shutdown(cx)
+  }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Transport for Stream<S> {}
+
 #[pin_project(project = DeviceProj)]
 enum Device {
   Tty(#[pin] SerialStream, String),
   Stream(#[pin] TcpStream),
+  Transport(Box<dyn Transport>),
 }
 
 impl fmt::Debug for Device {
@@ -26,6 +92,7 @@ impl fmt::Debug for Device {
     match self {
       Self::Tty(tty, _) => tty.fmt(f),
       Self::Stream(stream) => stream.fmt(f),
+      Self::Transport(_) => f.debug_tuple("Transport").finish_non_exhaustive(),
     }
   }
 }
@@ -35,6 +102,7 @@ impl AsyncWrite for Device {
     match self.project() {
       DeviceProj::Tty(tty, _) => tty.poll_write(cx, buf),
       DeviceProj::Stream(stream) => stream.poll_write(cx, buf),
+      DeviceProj::Transport(transport) => Pin::new(transport).poll_write(cx, buf),
     }
   }
 
@@ -42,6 +110,7 @@ impl AsyncWrite for Device {
     match self.project() {
       DeviceProj::Tty(tty, _) => tty.poll_flush(cx),
       DeviceProj::Stream(stream) => stream.poll_flush(cx),
+      DeviceProj::Transport(transport) => Pin::new(transport).poll_flush(cx),
     }
   }
 
@@ -49,6 +118,7 @@ impl AsyncWrite for Device {
     match self.project() {
       DeviceProj::Tty(tty, _) => tty.poll_shutdown(cx),
       DeviceProj::Stream(stream) => stream.poll_shutdown(cx),
+      DeviceProj::Transport(transport) => Pin::new(transport).poll_shutdown(cx),
     }
   }
 }
@@ -60,16 +130,18 @@ impl AsyncRead for Device {
     match this {
       DeviceProj::Tty(tty, _) => tty.poll_read(cx, buf),
       DeviceProj::Stream(stream) => stream.poll_read(cx, buf),
+      DeviceProj::Transport(transport) => Pin::new(transport).poll_read(cx, buf),
     }
   }
 }
 
-/// An Optolink connection via either a serial or TCP connection.
+/// An Optolink connection via either a serial connection, a TCP connection or a custom `Transport`.
 #[derive(Debug)]
 #[pin_project]
 pub struct Optolink {
   #[pin]
   device: Device,
+  config: ProtocolConfig,
 }
 
 impl Optolink {
@@ -109,7 +181,7 @@ impl Optolink {
       },
     };
 
-    Ok(Optolink { device: Device::Tty(serial_port, port.to_owned()) })
+    Ok(Optolink { device: Device::Tty(serial_port, port.to_owned()), config: ProtocolConfig::default() })
   }
 
   /// Connects to a device via TCP.
@@ -136,7 +208,51 @@ impl Optolink {
       )
     })?;
 
-    Ok(Optolink { device: Device::Stream(stream) })
+    Ok(Optolink { device: Device::Stream(stream), config: ProtocolConfig::default() })
+  }
+
+  /// Creates a connection using any stream, e.g. a `tokio::io::DuplexStream`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use vcontrol::Optolink;
+  ///
+  /// let (stream, _) = tokio::io::duplex(64);
+  /// let device = Optolink::from_stream(stream);
+  /// ```
+  pub fn from_stream<S>(stream: S) -> Optolink
+  where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+  {
+    Self::from_transport(Stream(stream))
+  }
+
+  /// Creates a connection using a custom `Transport`.
+  pub fn from_transport<T>(transport: T) -> Optolink
+  where
+    T: Transport + 'static,
+  {
+    Optolink { device: Device::Transport(Box::new(transport)), config: ProtocolConfig::default() }
+  }
+
+  /// Records all bytes sent and received on this connection to `writer`, see [`Recorder`].
+  pub fn record<W>(self, writer: W) -> Optolink
+  where
+    W: io::Write + Send + 'static,
+  {
+    let config = self.config;
+    Optolink { device: Device::Transport(Box::new(Recorder::new(self, writer))), config }
+  }
+
+  /// Returns the timeouts and retry limits used by protocol operations on this connection.
+  pub fn protocol_config(&self) -> ProtocolConfig {
+    self.config
+  }
+
+  /// Sets the timeouts and retry limits used by protocol operations on this connection.
+  pub fn set_protocol_config(&mut self, config: ProtocolConfig) {
+    self.config = config;
   }
 
   /// Purge all contents of the input buffer.
@@ -159,9 +275,11 @@ impl Optolink {
 
         Ok(())
       },
+      Device::Transport(ref mut transport) => transport.purge().await,
     }
   }
 
+  /// Re-initialize the connection after an error.
   pub async fn reinitialize(&mut self) -> Result<(), io::Error> {
     log::trace!("Optolink::reinitialize(…)");
 
@@ -191,6 +309,7 @@ impl Optolink {
         Ok(tty.set_exclusive(true)?)
       },
       Device::Stream(_) => Ok(()),
+      Device::Transport(ref mut transport) => transport.reinitialize().await,
     }
   }
 }
@@ -218,3 +337,58 @@ impl AsyncRead for Optolink {
     self.project().device.poll_read(cx, buf)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use tokio::io::{AsyncReadExt, AsyncWriteExt, duplex};
+
+  use super::*;
+
+  #[tokio::test]
+  async fn purge_stream() {
+    let (stream, mut other) = duplex(64);
+    let mut optolink = Optolink::from_stream(stream);
+
+    other.write_all(&[0x05, 0x05, 0x05]).await.unwrap();
+    optolink.purge().await.unwrap();
+
+    other.write_all(&[0x06]).await.unwrap();
+    assert_eq!(optolink.read_u8().await.unwrap(), 0x06);
+  }
+
+  #[tokio::test]
+  async fn custom_transport() {
+    struct Failing(tokio::io::DuplexStream);
+
+    impl AsyncRead for Failing {
+      fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+      }
+    }
+
+    impl AsyncWrite for Failing {
+      fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+      }
+
+      fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+      }
+
+      fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+      }
+    }
+
+    impl Transport for Failing {
+      fn reinitialize(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+        Box::pin(async { Err(io::Error::other("reinitialized")) })
+      }
+    }
+
+    let (stream, _other) = duplex(64);
+    let mut optolink = Optolink::from_transport(Failing(stream));
+
+    assert_eq!(optolink.reinitialize().await.unwrap_err().to_string(), "reinitialized");
+  }
+}
@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::{
+  sync::broadcast,
+  time::{Instant, sleep, sleep_until},
+};
+
+use crate::{Error, OutputValue, VControl};
+
+/// A changed value published by a `Poller`.
+#[derive(Debug, Clone)]
+pub struct Update {
+  /// The name of the command.
+  pub command: String,
+  /// The new value.
+  pub value: OutputValue,
+}
+
+#[derive(Debug)]
+struct Schedule {
+  interval: Duration,
+  next: Instant,
+  last: Option<OutputValue>,
+}
+
+/// Periodically reads commands from a `VControl` and publishes changed values.
+///
+/// All commands which are due at the same time are read together using `VControl::get_many`.
+/// If reading fails, the connection is renegotiated on the next attempt, which is delayed
+/// using an exponential backoff.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use vcontrol::{Optolink, Poller, VControl};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let vcontrol = VControl::connect(Optolink::open("/dev/ttyUSB0").await?).await?;
+///
+/// let mut poller = Poller::new(vcontrol);
+/// poller.add("Gemischte_AT", Duration::from_secs(60))?;
+///
+/// let mut updates = poller.subscribe();
+/// tokio::spawn(poller.run());
+///
+/// while let Ok(update) = updates.recv().await {
+///   println!("{}: {}", update.command, update.value);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Poller {
+  vcontrol: VControl,
+  schedules: BTreeMap<String, Schedule>,
+  sender: broadcast::Sender<Update>,
+  min_backoff: Duration,
+  max_backoff: Duration,
+  backoff: Option<Duration>,
+}
+
+impl Poller {
+  /// Creates a new `Poller` without any commands.
+  pub fn new(vcontrol: VControl) -> Self {
+    let (sender, _) = broadcast::channel(64);
+
+    Self {
+      vcontrol,
+      schedules: BTreeMap::new(),
+      sender,
+      min_backoff: Duration::from_secs(1),
+      max_backoff: Duration::from_secs(300),
+      backoff: None,
+    }
+  }
+
+  /// Sets the delay after the first failed poll, which is doubled for each subsequent failure up to `max`.
+  pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+    self.min_backoff = min;
+    self.max_backoff = max.max(min);
+    self
+  }
+
+  /// Polls the given command every `interval`.
+  ///
+  /// If the command was already added, the shorter of both intervals is used.
+  pub fn add(&mut self, command: &str, interval: Duration) -> Result<&mut Self, Error> {
+    self.vcontrol.command_by_name(command)?.check_readable()?;
+
+    let now = Instant::now();
+    self
+      .schedules
+      .entry(command.to_owned())
+      .and_modify(|schedule| {
+        if interval < schedule.interval {
+          schedule.interval = interval;
+          schedule.next = schedule.next.min(now + interval);
+        }
+      })
+      .or_insert(Schedule { interval, next: now, last: None });
+
+    Ok(self)
+  }
+
+  /// Stops polling the given command.
+  pub fn remove(&mut self, command: &str) -> &mut Self {
+    self.schedules.remove(command);
+    self
+  }
+
+  /// Returns a receiver for all changed values.
+  pub fn subscribe(&self) -> broadcast::Receiver<Update> {
+    self.sender.subscribe()
+  }
+
+  /// Returns the last value read for the given command.
+  pub fn last_value(&self, command: &str) -> Option<&OutputValue> {
+    self.schedules.get(command)?.last.as_ref()
+  }
+
+  /// Returns the time at which the next command is due.
+  pub fn next_due(&self) -> Option<Instant> {
+    self.schedules.values().map(|schedule| schedule.next).min()
+  }
+
+  /// Reads all commands which are currently due and publishes changed values.
+  pub async fn poll(&mut self) -> Result<(), Error> {
+    log::trace!("Poller::poll()");
+
+    let now = Instant::now();
+    let due = self
+      .schedules
+      .iter()
+      .filter(|(_, schedule)| schedule.next <= now)
+      .map(|(command, _)| command.as_str())
+      .collect::<Vec<_>>();
+
+    if due.is_empty() {
+      return Ok(());
+    }
+
+    let values = self.vcontrol.get_many(&due).await?;
+    let due = due.into_iter().map(str::to_owned).collect::<Vec<_>>();
+
+    for (command, value) in due.into_iter().zip(values) {
+      let schedule = self.schedules.get_mut(&command).expect("due command is scheduled");
+      schedule.next = now + schedule.interval;
+
+      let value = match value {
+        Ok(value) => value,
+        Err(err) => {
+          log::warn!("Failed to read '{command}': {err}");
+          continue;
+        },
+      };
+
+      if schedule.last.as_ref().is_some_and(|last| last.value == value.value) {
+        continue;
+      }
+
+      schedule.last = Some(value.clone());
+
+      // Sending only fails if there are no receivers, in which case the update is simply dropped.
+      let _ = self.sender.send(Update { command, value });
+    }
+
+    Ok(())
+  }
+
+  /// Polls all commands indefinitely.
+  pub async fn run(mut self) {
+    loop {
+      match self.next_due() {
+        Some(next) => sleep_until(next).await,
+        None => sleep(self.min_backoff).await,
+      }
+
+      match self.poll().await {
+        Ok(()) => self.backoff = None,
+        Err(err) => {
+          let backoff = self.backoff.map_or(self.min_backoff, |backoff| (backoff * 2).min(self.max_backoff));
+          self.backoff = Some(backoff);
+
+          log::warn!("Polling failed, retrying in {backoff:?}: {err}");
+          sleep(backoff).await;
+        },
+      }
+    }
+  }
+
+  /// Returns the underlying `VControl`.
+  pub fn into_inner(self) -> VControl {
+    self.vcontrol
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    Value,
+    sim::{Simulator, connect, vscot_ho1},
+  };
+
+  #[tokio::test(start_paused = true)]
+  async fn publish_changes() {
+    let simulator = Simulator::from_device(vscot_ho1());
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+
+    let vcontrol = VControl::connect(connect(simulator.clone()).await).await.unwrap();
+
+    let mut poller = Poller::new(vcontrol);
+    poller.add("TiefpassTemperaturwert_ATS", Duration::from_secs(10)).unwrap();
+    poller.add("TiefpassTemperaturwert_ATS", Duration::from_millis(50)).unwrap();
+    assert!(poller.add("Unbekannt", Duration::from_secs(5)).is_err());
+
+    let mut updates = poller.subscribe();
+
+    poller.poll().await.unwrap();
+    let update = updates.try_recv().unwrap();
+    assert_eq!(update.command, "TiefpassTemperaturwert_ATS");
+    assert_eq!(update.value.value, Value::Double(-3.5));
+
+    // Not due yet.
+    poller.poll().await.unwrap();
+    tokio::time::advance(Duration::from_millis(50)).await;
+
+    // Due, but unchanged.
+    poller.poll().await.unwrap();
+    assert!(updates.try_recv().is_err());
+
+    simulator.write(0x5525, &(42i16).to_le_bytes());
+    tokio::time::advance(Duration::from_millis(50)).await;
+
+    poller.poll().await.unwrap();
+    assert_eq!(updates.try_recv().unwrap().value.value, Value::Double(4.2));
+    assert!(updates.try_recv().is_err());
+  }
+}
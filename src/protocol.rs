@@ -1,26 +1,121 @@
-use std::{fmt, io};
+use std::{fmt, future::Future, io, time::Duration};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::Optolink;
 
-mod vs1;
+mod error;
+pub(crate) use self::error::Hex;
+pub use self::error::ProtocolError;
+
+pub(crate) mod vs1;
 use self::vs1::Vs1;
 
-mod vs2;
+pub(crate) mod vs2;
+pub use self::vs2::{Function, MessageType};
 use self::vs2::Vs2;
 
-#[derive(Debug, Clone, Copy)]
+pub(crate) mod gwg;
+use self::gwg::Gwg;
+
+/// Timeouts and retry limits for protocol operations.
+///
+/// Operations exceeding a timeout or retry limit fail with an `io::ErrorKind::TimedOut` error,
+/// which is converted to `Error::Timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolConfig {
+  /// Maximum time to wait for a single read.
+  pub read_timeout: Duration,
+  /// Maximum time to wait for a single write.
+  pub write_timeout: Duration,
+  /// Maximum time to wait for the protocol negotiation, including synchronization.
+  pub negotiation_timeout: Duration,
+  /// Maximum number of times a request is repeated after an invalid or unacknowledged response.
+  pub retries: usize,
+  /// Whether to read back written values to verify them.
+  pub verify_writes: bool,
+  /// Whether `Protocol::request` may use functions writing to memory other than virtual addresses.
+  pub allow_raw_writes: bool,
+  /// Maximum number of bytes read with a single request when reading several commands at once.
+  ///
+  /// Real controllers stop responding to longer reads, even if the protocol allows them.
+  pub max_batch_len: usize,
+}
+
+impl Default for ProtocolConfig {
+  fn default() -> Self {
+    Self {
+      read_timeout: Duration::from_secs(5),
+      write_timeout: Duration::from_secs(5),
+      negotiation_timeout: Duration::from_secs(10),
+      retries: 3,
+      verify_writes: false,
+      allow_raw_writes: false,
+      max_batch_len: 119,
+    }
+  }
+}
+
+/// Runs `future`, failing with `io::ErrorKind::TimedOut` if it does not complete within `duration`.
+pub(crate) async fn timeout<T>(
+  duration: Duration,
+  operation: &str,
+  future: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+  match tokio::time::timeout(duration, future).await {
+    Ok(res) => res,
+    Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, format!("{operation} timed out after {duration:?}"))),
+  }
+}
+
+/// Reads exactly `buf.len()` bytes within the configured read timeout.
+pub(crate) async fn read_exact(o: &mut Optolink, buf: &mut [u8]) -> io::Result<()> {
+  let read_timeout = o.protocol_config().read_timeout;
+  timeout(read_timeout, "read", async { o.read_exact(buf).await.map(|_| ()) }).await
+}
+
+/// Writes and flushes all `bytes` within the configured write timeout.
+pub(crate) async fn write_all(o: &mut Optolink, bytes: &[u8]) -> io::Result<()> {
+  let write_timeout = o.protocol_config().write_timeout;
+  timeout(write_timeout, "write", async {
+    o.write_all(bytes).await?;
+    o.flush().await
+  })
+  .await
+}
+
+/// Returns the error for a request which was repeated `retries` times without success.
+pub(crate) fn retries_exceeded(operation: &str, retries: usize) -> io::Error {
+  io::Error::new(io::ErrorKind::TimedOut, format!("{operation} failed after {retries} retries"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Protocol {
   Vs1,
   Vs2,
+  /// The protocol of legacy controllers, e.g. Vitodens 200 or Vitola with GWG.
+  Gwg,
 }
 
 impl Protocol {
   /// Try detecting the protocol automatically.
+  ///
+  /// Each protocol is tried within the negotiation timeout of the `Optolink`'s `ProtocolConfig`,
+  /// checking `Vs1` before `Gwg` and falling back to `Vs1` if the controller answers neither.
   pub async fn detect(o: &mut Optolink) -> Option<Self> {
     if Vs2::negotiate(o).await.is_ok() {
       return Some(Self::Vs2);
     }
 
+    // Neither `Vs1` nor `Gwg` acknowledge the negotiation, so check which requests are answered.
+    if Vs1::detect(o).await.is_ok() {
+      return Some(Self::Vs1);
+    }
+
+    if Gwg::detect(o).await.is_ok() {
+      return Some(Self::Gwg);
+    }
+
     if Vs1::negotiate(o).await.is_ok() {
       return Some(Self::Vs1);
     }
@@ -33,6 +128,19 @@ impl Protocol {
     match self {
       Self::Vs1 => Vs1::negotiate(o).await,
       Self::Vs2 => Vs2::negotiate(o).await,
+      Self::Gwg => Gwg::negotiate(o).await,
+    }
+  }
+
+  /// Returns the maximum number of bytes which can be read or written with a single request.
+  pub fn max_payload_len(&self) -> usize {
+    match self {
+      // The request contains the length as a single byte.
+      Self::Vs1 => u8::MAX as usize,
+      // The telegram length byte also includes the 5 header bytes.
+      Self::Vs2 => u8::MAX as usize - 5,
+      // The request contains the length as a single byte.
+      Self::Gwg => u8::MAX as usize,
     }
   }
 
@@ -41,6 +149,7 @@ impl Protocol {
     match self {
       Self::Vs1 => Vs1::get(o, addr, buf).await,
       Self::Vs2 => Vs2::get(o, addr, buf).await,
+      Self::Gwg => Gwg::get(o, addr, buf).await,
     }
   }
 
@@ -49,6 +158,73 @@ impl Protocol {
     match self {
       Self::Vs1 => Vs1::set(o, addr, value).await,
       Self::Vs2 => Vs2::set(o, addr, value).await,
+      Self::Gwg => Gwg::set(o, addr, value).await,
+    }
+  }
+
+  /// Sends a request with the given `function` to the address `addr`.
+  ///
+  /// If the function sends a payload, e.g. `Function::PhysicalWrite`, `payload` is sent. If it returns
+  /// a payload, e.g. `Function::EepromRead`, the response is read into `buf`. `Function::RemoteProcedureCall`
+  /// does both. Write functions other than the virtual ones, e.g. `Function::PhysicalWrite` or
+  /// `Function::EepromWrite`, are rejected unless `ProtocolConfig::allow_raw_writes` is set.
+  ///
+  /// `Vs1` only supports the virtual, GFA and process functions, `Gwg` only the virtual and physical functions.
+  pub async fn request(
+    &self,
+    o: &mut Optolink,
+    function: Function,
+    addr: u16,
+    payload: &[u8],
+    buf: &mut [u8],
+  ) -> Result<(), io::Error> {
+    if function.writes_raw() && !o.protocol_config().allow_raw_writes {
+      return Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("{function:?} is disabled, enable `ProtocolConfig::allow_raw_writes` to use it"),
+      ));
+    }
+
+    self.request_unchecked(o, function, addr, payload, buf).await
+  }
+
+  /// Sends a request like `Protocol::request`, but without rejecting raw writes.
+  ///
+  /// Used for commands, which are written using the function defined for them.
+  pub(crate) async fn request_unchecked(
+    &self,
+    o: &mut Optolink,
+    function: Function,
+    addr: u16,
+    payload: &[u8],
+    buf: &mut [u8],
+  ) -> Result<(), io::Error> {
+    let len = payload.len().max(buf.len());
+    if len > self.max_payload_len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{len} bytes exceed the maximum payload length of {}", self.max_payload_len()),
+      ));
+    }
+
+    match (self, function) {
+      (Self::Vs1, Function::VirtualRead) => Vs1::read(o, vs1::Function::VirtualRead, addr, buf).await,
+      (Self::Vs1, Function::VirtualWrite) => Vs1::write(o, vs1::Function::VirtualWrite, addr, payload).await,
+      (Self::Vs1, Function::GfaRead) => Vs1::read(o, vs1::Function::GfaRead, addr, buf).await,
+      (Self::Vs1, Function::GfaWrite) => Vs1::write(o, vs1::Function::GfaWrite, addr, payload).await,
+      (Self::Vs1, Function::ProcessRead) => Vs1::read(o, vs1::Function::ProcessRead, addr, buf).await,
+      (Self::Vs1, Function::ProcessWrite) => Vs1::write(o, vs1::Function::ProcessWrite, addr, payload).await,
+      (Self::Vs1, function) => {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{function:?} is not supported by {self}")))
+      },
+      (Self::Vs2, function) => Vs2::request(o, function, addr, payload, buf).await,
+      (Self::Gwg, Function::VirtualRead) => Gwg::read(o, gwg::Function::VirtualRead, addr, buf).await,
+      (Self::Gwg, Function::VirtualWrite) => Gwg::write(o, gwg::Function::VirtualWrite, addr, payload).await,
+      (Self::Gwg, Function::PhysicalRead) => Gwg::read(o, gwg::Function::PhysicalRead, addr, buf).await,
+      (Self::Gwg, Function::PhysicalWrite) => Gwg::write(o, gwg::Function::PhysicalWrite, addr, payload).await,
+      (Self::Gwg, function) => {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{function:?} is not supported by {self}")))
+      },
     }
   }
 }
@@ -58,7 +234,142 @@ impl fmt::Display for Protocol {
     match self {
       Self::Vs1 => "VS1",
       Self::Vs2 => "VS2",
+      Self::Gwg => "GWG",
     }
     .fmt(f)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn silent_optolink() -> (Optolink, tokio::io::DuplexStream) {
+    let (stream, other) = tokio::io::duplex(64);
+    let mut optolink = Optolink::from_stream(stream);
+    optolink.set_protocol_config(ProtocolConfig {
+      read_timeout: Duration::from_millis(50),
+      write_timeout: Duration::from_millis(50),
+      negotiation_timeout: Duration::from_millis(100),
+      retries: 1,
+      ..ProtocolConfig::default()
+    });
+    (optolink, other)
+  }
+
+  #[tokio::test]
+  async fn negotiate_timeout() {
+    let (mut optolink, _other) = silent_optolink();
+
+    let err = Protocol::Vs2.negotiate(&mut optolink).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(matches!(crate::Error::from(err), crate::Error::Timeout(_)));
+  }
+
+  #[tokio::test]
+  async fn get_timeout() {
+    let (mut optolink, _other) = silent_optolink();
+
+    let mut buf = [0; 2];
+    for protocol in [Protocol::Vs1, Protocol::Vs2, Protocol::Gwg] {
+      let err = protocol.get(&mut optolink, 0x00F8, &mut buf).await.unwrap_err();
+      assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+  }
+
+  #[tokio::test]
+  async fn sync_eof() {
+    use tokio::io::AsyncReadExt;
+
+    for protocol in [Protocol::Vs1, Protocol::Gwg] {
+      let (mut optolink, mut other) = silent_optolink();
+
+      // Close the connection after receiving the reset.
+      let peer = tokio::spawn(async move {
+        other.read_u8().await.unwrap();
+      });
+
+      let err = protocol.get(&mut optolink, 0x00F8, &mut [0; 2]).await.unwrap_err();
+      assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+      peer.await.unwrap();
+    }
+  }
+
+  #[tokio::test]
+  async fn get_address_mismatch() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut optolink, mut other) = silent_optolink();
+
+    let peer = tokio::spawn(async move {
+      let mut request = [0; 8];
+      other.read_exact(&mut request).await.unwrap();
+
+      let mut response = vec![vs2::ACK, vs2::LEADIN, 0x07, 0x01, 0x01, 0x00, 0xF9, 0x02, 0x20, 0xCB];
+      response.push(vs2::wrapping_sum(&response[2..]));
+      other.write_all(&response).await.unwrap();
+
+      let mut ack = [0];
+      other.read_exact(&mut ack).await.unwrap();
+      other
+    });
+
+    let mut buf = [0; 2];
+    let err = Protocol::Vs2.get(&mut optolink, 0x00F8, &mut buf).await.unwrap_err();
+    peer.await.unwrap();
+
+    match crate::Error::from(err) {
+      crate::Error::Protocol(ProtocolError::AddressMismatch { expected, actual, telegram }) => {
+        assert_eq!(expected, 0x00F8);
+        assert_eq!(actual, 0x00F9);
+        assert_eq!(telegram.len(), 10);
+      },
+      err => panic!("expected address mismatch, got {err:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn request_raw_write_disabled() {
+    let (mut optolink, _other) = silent_optolink();
+
+    let err = Protocol::Vs2.request(&mut optolink, Function::EepromWrite, 0x0000, &[0x01], &mut []).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+    for function in [Function::PhysicalWrite, Function::XramWrite, Function::PortWrite, Function::GfaWrite] {
+      let err = Protocol::Vs2.request(&mut optolink, function, 0x0000, &[0x01], &mut []).await.unwrap_err();
+      assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    let err = Protocol::Vs1.request(&mut optolink, Function::PhysicalRead, 0x0000, &[], &mut [0]).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+  }
+
+  #[tokio::test]
+  async fn request_remote_procedure_call() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut optolink, mut other) = silent_optolink();
+
+    let peer = tokio::spawn(async move {
+      let mut request = [0; 10];
+      other.read_exact(&mut request).await.unwrap();
+      assert_eq!(request[..9], [vs2::LEADIN, 0x07, 0x00, 0x07, 0x0B, 0x00, 0x02, 0x12, 0x34]);
+      assert_eq!(request[9], vs2::wrapping_sum(&request[1..9]));
+
+      let mut response = vec![vs2::ACK, vs2::LEADIN, 0x06, 0x01, 0x07, 0x0B, 0x00, 0x01, 0x56];
+      response.push(vs2::wrapping_sum(&response[2..]));
+      other.write_all(&response).await.unwrap();
+
+      let mut ack = [0];
+      other.read_exact(&mut ack).await.unwrap();
+      other
+    });
+
+    let mut buf = [0; 1];
+    Protocol::Vs2.request(&mut optolink, Function::RemoteProcedureCall, 0x0B00, &[0x12, 0x34], &mut buf).await.unwrap();
+    peer.await.unwrap();
+
+    assert_eq!(buf, [0x56]);
+  }
+}
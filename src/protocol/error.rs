@@ -0,0 +1,133 @@
+use std::{error::Error as StdError, fmt, io};
+
+/// An error in the communication with the heating controller.
+///
+/// Each variant carries the raw bytes of the offending telegram, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProtocolError {
+  /// A received telegram did not start with the expected lead-in byte.
+  InvalidLeadin { byte: u8 },
+  /// The checksum of a received telegram did not match its contents.
+  InvalidChecksum { expected: u8, actual: u8, telegram: Vec<u8> },
+  /// A sent telegram was not acknowledged, even after renegotiating.
+  Nack { telegram: Vec<u8> },
+  /// A sent telegram was answered with neither `ACK` nor `NACK`.
+  UnexpectedStatus { status: u8, telegram: Vec<u8> },
+  /// The protocol negotiation was answered with neither `ACK` nor `NACK`.
+  NegotiationFailed { status: u8 },
+  /// A received telegram has an unknown message type.
+  UnknownMessageType { message_type: u8, telegram: Vec<u8> },
+  /// A received telegram has an unknown function.
+  UnknownFunction { function: u8, telegram: Vec<u8> },
+  /// A received telegram is not a response, e.g. an error message from the controller.
+  UnexpectedMessageType { message_type: u8, telegram: Vec<u8> },
+  /// A received telegram does not answer the function of the request.
+  FunctionMismatch { expected: u8, actual: u8, telegram: Vec<u8> },
+  /// A received telegram does not answer the address of the request.
+  AddressMismatch { expected: u16, actual: u16, telegram: Vec<u8> },
+  /// A length in a received telegram does not match the expected length.
+  LengthMismatch { expected: usize, actual: usize, telegram: Vec<u8> },
+}
+
+impl ProtocolError {
+  /// Returns the raw bytes of the telegram which caused this error, if any.
+  pub fn telegram(&self) -> Option<&[u8]> {
+    match self {
+      Self::InvalidLeadin { .. } | Self::NegotiationFailed { .. } => None,
+      Self::InvalidChecksum { telegram, .. }
+      | Self::Nack { telegram }
+      | Self::UnexpectedStatus { telegram, .. }
+      | Self::UnknownMessageType { telegram, .. }
+      | Self::UnknownFunction { telegram, .. }
+      | Self::UnexpectedMessageType { telegram, .. }
+      | Self::FunctionMismatch { telegram, .. }
+      | Self::AddressMismatch { telegram, .. }
+      | Self::LengthMismatch { telegram, .. } => Some(telegram),
+    }
+  }
+
+  /// Returns whether this error is most likely caused by a transmission glitch,
+  /// in which case repeating the request may succeed.
+  pub fn is_retryable(&self) -> bool {
+    matches!(self, Self::InvalidLeadin { .. } | Self::InvalidChecksum { .. } | Self::Nack { .. })
+  }
+}
+
+/// Displays bytes as space-separated hex.
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, byte) in self.0.iter().enumerate() {
+      if i > 0 {
+        write!(f, " ")?;
+      }
+      write!(f, "{byte:02X}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl fmt::Display for ProtocolError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::InvalidLeadin { byte } => write!(f, "telegram leadin expected, got 0x{byte:02X}")?,
+      Self::InvalidChecksum { expected, actual, .. } => write!(f, "invalid checksum: {expected} != {actual}")?,
+      Self::Nack { .. } => write!(f, "telegram not acknowledged")?,
+      Self::UnexpectedStatus { status, .. } => write!(f, "send telegram failed, got status 0x{status:02X}")?,
+      Self::NegotiationFailed { status } => write!(f, "protocol negotiation failed, got status 0x{status:02X}")?,
+      Self::UnknownMessageType { message_type, .. } => write!(f, "unknown message identifier: {message_type}")?,
+      Self::UnknownFunction { function, .. } => write!(f, "unknown function: {function}")?,
+      Self::UnexpectedMessageType { message_type, .. } => {
+        write!(f, "expected response message identifier, got {message_type}")?
+      },
+      Self::FunctionMismatch { expected, actual, .. } => write!(f, "expected function {expected}, got {actual}")?,
+      Self::AddressMismatch { expected, actual, .. } => {
+        write!(f, "expected address 0x{expected:04X}, got 0x{actual:04X}")?
+      },
+      Self::LengthMismatch { expected, actual, .. } => write!(f, "expected length {expected}, got {actual}")?,
+    }
+
+    if let Some(telegram) = self.telegram() {
+      write!(f, " (telegram: {})", Hex(telegram))?;
+    }
+
+    Ok(())
+  }
+}
+
+impl StdError for ProtocolError {}
+
+impl From<ProtocolError> for io::Error {
+  fn from(err: ProtocolError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display() {
+    let err = ProtocolError::InvalidChecksum { expected: 0x10, actual: 0x11, telegram: vec![0x41, 0x05, 0x00, 0x11] };
+    assert_eq!(err.to_string(), "invalid checksum: 16 != 17 (telegram: 41 05 00 11)");
+  }
+
+  #[test]
+  fn into_error() {
+    let err = ProtocolError::Nack { telegram: vec![0x41, 0x05] };
+    let io_err = io::Error::from(err.clone());
+    assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+
+    match crate::Error::from(io_err) {
+      crate::Error::Protocol(protocol_err) => {
+        assert_eq!(protocol_err, err);
+        assert!(protocol_err.is_retryable());
+      },
+      err => panic!("expected protocol error, got {err:?}"),
+    }
+  }
+}
@@ -0,0 +1,156 @@
+use std::{
+  io,
+  time::{Duration, Instant},
+};
+
+use crate::{
+  Optolink,
+  commands::system,
+  protocol::{
+    read_exact, retries_exceeded, timeout,
+    vs1::{RESET, SYNC},
+    write_all,
+  },
+};
+
+/// Terminates a read request.
+pub(crate) const EOT: u8 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+#[repr(u8)]
+pub(crate) enum Function {
+  VirtualRead   = 199,
+  VirtualWrite  = 196,
+  PhysicalRead  = 203,
+  PhysicalWrite = 200,
+}
+
+#[derive(Debug)]
+pub enum Gwg {}
+
+impl Gwg {
+  async fn sync(o: &mut Optolink) -> Result<(), io::Error> {
+    log::trace!("Gwg::sync(…)");
+
+    let negotiation_timeout = o.protocol_config().negotiation_timeout;
+
+    timeout(negotiation_timeout, "GWG synchronization", async {
+      let mut buf = [0xff];
+
+      Self::negotiate(o).await?;
+
+      loop {
+        match read_exact(o, &mut buf).await {
+          Ok(()) if buf == [SYNC] => {
+            o.purge().await?;
+            return Ok(());
+          },
+          // Keep waiting for `SYNC` until the synchronization times out.
+          Ok(()) => (),
+          Err(err) if err.kind() == io::ErrorKind::TimedOut => (),
+          Err(err) => return Err(err),
+        }
+      }
+    })
+    .await
+  }
+
+  pub async fn negotiate(o: &mut Optolink) -> Result<(), io::Error> {
+    log::trace!("Gwg::negotiate(…)");
+
+    o.purge().await?;
+    write_all(o, &[RESET]).await
+  }
+
+  /// Checks whether the controller answers a GWG request for the device identifier.
+  ///
+  /// `Vs1` controllers also send `SYNC` (`0x05`) but ignore GWG requests.
+  pub async fn detect(o: &mut Optolink) -> Result<(), io::Error> {
+    log::trace!("Gwg::detect(…)");
+
+    let negotiation_timeout = o.protocol_config().negotiation_timeout;
+
+    let mut buf = [0; 2];
+    timeout(negotiation_timeout, "GWG detection", Self::get(o, system::DEVICE_ID.addr, &mut buf)).await?;
+
+    // A controller which does not understand the request keeps sending `SYNC`.
+    if buf == [SYNC; 2] {
+      return Err(io::Error::new(io::ErrorKind::InvalidData, "GWG detection only received SYNC"));
+    }
+
+    Ok(())
+  }
+
+  /// GWG telegrams only contain the low byte of the address.
+  fn addr(addr: u16) -> Result<u8, io::Error> {
+    u8::try_from(addr).map_err(|_| {
+      io::Error::new(io::ErrorKind::InvalidInput, format!("address 0x{addr:04X} exceeds the GWG address range"))
+    })
+  }
+
+  pub async fn get(o: &mut Optolink, addr: u16, buf: &mut [u8]) -> Result<(), io::Error> {
+    log::trace!("Gwg::get(…)");
+
+    Self::read(o, Function::VirtualRead, addr, buf).await
+  }
+
+  /// Reads `buf.len()` bytes at the address `addr` using the given read `function`.
+  pub(crate) async fn read(o: &mut Optolink, function: Function, addr: u16, buf: &mut [u8]) -> Result<(), io::Error> {
+    log::trace!("Gwg::read({function:?}, …)");
+
+    let request = [0x01, function as u8, Self::addr(addr)?, buf.len() as u8, EOT];
+
+    Self::sync(o).await?;
+
+    let retries = o.protocol_config().retries;
+
+    for _ in 0..=retries {
+      write_all(o, &request).await?;
+
+      let read_start = Instant::now();
+      read_exact(o, buf).await?;
+      let read_time = read_start.elapsed();
+
+      // Like with `Vs1`, `SYNC` (`0x05`) in a slow response is most likely a synchronization byte.
+      if !buf.contains(&SYNC) || read_time < Duration::from_millis(500 * buf.len() as u64) {
+        return Ok(());
+      }
+
+      o.purge().await?;
+    }
+
+    Err(retries_exceeded("GWG read", retries))
+  }
+
+  pub async fn set(o: &mut Optolink, addr: u16, value: &[u8]) -> Result<(), io::Error> {
+    log::trace!("Gwg::set(…)");
+
+    Self::write(o, Function::VirtualWrite, addr, value).await
+  }
+
+  /// Writes `value` to the address `addr` using the given write `function`.
+  pub(crate) async fn write(o: &mut Optolink, function: Function, addr: u16, value: &[u8]) -> Result<(), io::Error> {
+    log::trace!("Gwg::write({function:?}, …)");
+
+    let mut request = vec![0x01, function as u8, Self::addr(addr)?, value.len() as u8];
+    request.extend(value);
+
+    Self::sync(o).await?;
+
+    let retries = o.protocol_config().retries;
+
+    for _ in 0..=retries {
+      write_all(o, &request).await?;
+
+      let mut buf = [0xff];
+      read_exact(o, &mut buf).await?;
+
+      if buf == [0x00] {
+        return Ok(());
+      }
+    }
+
+    Err(retries_exceeded("GWG write", retries))
+  }
+}
@@ -3,17 +3,21 @@ use std::{
   time::{Duration, Instant},
 };
 
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use num_enum::TryFromPrimitive;
 
-use crate::Optolink;
+use crate::{
+  Optolink,
+  commands::system,
+  protocol::{read_exact, retries_exceeded, timeout, write_all},
+};
 
-const RESET: u8 = 0x04;
-const SYNC: u8 = 0x05;
+pub(crate) const RESET: u8 = 0x04;
+pub(crate) const SYNC: u8 = 0x05;
 
-#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, TryFromPrimitive)]
 #[non_exhaustive]
 #[repr(u8)]
-enum Function {
+pub(crate) enum Function {
   VirtualRead  = 247,
   VirtualWrite = 244,
   GfaRead      = 107,
@@ -22,6 +26,20 @@ enum Function {
   ProcessWrite = 120,
 }
 
+impl Function {
+  /// Returns the equivalent `Vs2` function.
+  pub(crate) fn to_vs2(self) -> crate::Function {
+    match self {
+      Self::VirtualRead => crate::Function::VirtualRead,
+      Self::VirtualWrite => crate::Function::VirtualWrite,
+      Self::GfaRead => crate::Function::GfaRead,
+      Self::GfaWrite => crate::Function::GfaWrite,
+      Self::ProcessRead => crate::Function::ProcessRead,
+      Self::ProcessWrite => crate::Function::ProcessWrite,
+    }
+  }
+}
+
 #[derive(Debug)]
 pub enum Vs1 {}
 
@@ -29,27 +47,54 @@ impl Vs1 {
   async fn sync(o: &mut Optolink) -> Result<(), std::io::Error> {
     log::trace!("Vs1::sync(…)");
 
-    let mut buf = [0xff];
-
-    // Reset the Optolink connection to get a faster `SYNC` (`0x05`).
-    Self::negotiate(o).await?;
+    let negotiation_timeout = o.protocol_config().negotiation_timeout;
 
-    loop {
-      log::trace!("Vs1::sync(…) loop");
+    timeout(negotiation_timeout, "VS1 synchronization", async {
+      let mut buf = [0xff];
 
-      if o.read_exact(&mut buf).await.is_ok() && buf == [SYNC] {
-        o.purge().await?;
-        return Ok(());
+      // Reset the Optolink connection to get a faster `SYNC` (`0x05`).
+      Self::negotiate(o).await?;
+
+      loop {
+        log::trace!("Vs1::sync(…) loop");
+
+        match read_exact(o, &mut buf).await {
+          Ok(()) if buf == [SYNC] => {
+            o.purge().await?;
+            return Ok(());
+          },
+          // Keep waiting for `SYNC` until the synchronization times out.
+          Ok(()) => (),
+          Err(err) if err.kind() == io::ErrorKind::TimedOut => (),
+          Err(err) => return Err(err),
+        }
       }
-    }
+    })
+    .await
   }
 
   pub async fn negotiate(o: &mut Optolink) -> Result<(), io::Error> {
     log::trace!("Vs1::negotiate(…)");
 
     o.purge().await?;
-    o.write_all(&[RESET]).await?;
-    o.flush().await?;
+    write_all(o, &[RESET]).await
+  }
+
+  /// Checks whether the controller answers a `Vs1` request for the device identifier.
+  ///
+  /// `Gwg` controllers also send `SYNC` (`0x05`) but ignore `Vs1` requests.
+  pub async fn detect(o: &mut Optolink) -> Result<(), io::Error> {
+    log::trace!("Vs1::detect(…)");
+
+    let negotiation_timeout = o.protocol_config().negotiation_timeout;
+
+    let mut buf = [0; 2];
+    timeout(negotiation_timeout, "VS1 detection", Self::get(o, system::DEVICE_ID.addr, &mut buf)).await?;
+
+    // A controller which does not understand the request keeps sending `SYNC`.
+    if buf == [SYNC; 2] {
+      return Err(io::Error::new(io::ErrorKind::InvalidData, "VS1 detection only received SYNC"));
+    }
 
     Ok(())
   }
@@ -57,22 +102,30 @@ impl Vs1 {
   pub async fn get(o: &mut Optolink, addr: u16, buf: &mut [u8]) -> Result<(), io::Error> {
     log::trace!("Vs1::get(…)");
 
+    Self::read(o, Function::VirtualRead, addr, buf).await
+  }
+
+  /// Reads `buf.len()` bytes at the address `addr` using the given read `function`.
+  pub(crate) async fn read(o: &mut Optolink, function: Function, addr: u16, buf: &mut [u8]) -> Result<(), io::Error> {
+    log::trace!("Vs1::read({function:?}, …)");
+
     let mut vec = Vec::new();
-    vec.extend(&[0x01, Function::VirtualRead as u8]);
+    vec.extend(&[0x01, function as u8]);
     vec.extend(addr.to_be_bytes());
     vec.extend(&[buf.len() as u8]);
 
     Self::sync(o).await?;
 
-    loop {
-      log::trace!("Vs1::get(…) loop");
+    let retries = o.protocol_config().retries;
+
+    for _ in 0..=retries {
+      log::trace!("Vs1::read(…) loop");
 
-      o.write_all(&vec).await?;
-      o.flush().await?;
+      write_all(o, &vec).await?;
 
       let read_start = Instant::now();
 
-      o.read_exact(buf).await?;
+      read_exact(o, buf).await?;
 
       let stop = Instant::now();
 
@@ -82,10 +135,10 @@ impl Vs1 {
         let read_time = stop - read_start;
 
         log::debug!(
-          "Vs1::get(…) buf = {}",
+          "Vs1::read(…) buf = {}",
           buf.iter().map(|b| format!("{:02X}", b)).collect::<Vec<String>>().join(" ")
         );
-        log::debug!("Vs1::get(…) read_time = {:?}", read_time);
+        log::debug!("Vs1::read(…) read_time = {:?}", read_time);
 
         // Return `Ok` if the response was received in a short amount of time,
         // since then they most likely are not synchronization bytes.
@@ -98,29 +151,41 @@ impl Vs1 {
         return Ok(());
       }
     }
+
+    Err(retries_exceeded("VS1 read", retries))
   }
 
   pub async fn set(o: &mut Optolink, addr: u16, value: &[u8]) -> Result<(), io::Error> {
     log::trace!("Vs1::set(…)");
 
+    Self::write(o, Function::VirtualWrite, addr, value).await
+  }
+
+  /// Writes `value` to the address `addr` using the given write `function`.
+  pub(crate) async fn write(o: &mut Optolink, function: Function, addr: u16, value: &[u8]) -> Result<(), io::Error> {
+    log::trace!("Vs1::write({function:?}, …)");
+
     let mut vec = Vec::new();
-    vec.extend(&[0x01, Function::VirtualWrite as u8]);
+    vec.extend(&[0x01, function as u8]);
     vec.extend(addr.to_be_bytes());
     vec.extend(&[value.len() as u8]);
     vec.extend(value);
 
     Self::sync(o).await?;
 
-    loop {
-      o.write_all(&vec).await?;
-      o.flush().await?;
+    let retries = o.protocol_config().retries;
+
+    for _ in 0..=retries {
+      write_all(o, &vec).await?;
 
       let mut buf = [0xff];
-      o.read_exact(&mut buf).await?;
+      read_exact(o, &mut buf).await?;
 
       if buf == [0x00] {
         return Ok(());
       }
     }
+
+    Err(retries_exceeded("VS1 write", retries))
   }
 }
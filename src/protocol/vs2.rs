@@ -2,21 +2,25 @@ use core::fmt;
 use std::io;
 
 use num_enum::TryFromPrimitive;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use crate::{Optolink, commands::MAX_PAYLOAD_LEN};
+use crate::{
+  Optolink,
+  commands::MAX_PAYLOAD_LEN,
+  protocol::{ProtocolError, read_exact, timeout, write_all},
+};
 
-const LEADIN: u8 = 0x41;
+pub(crate) const LEADIN: u8 = 0x41;
 
-const RESET: [u8; 1] = [0x04];
-const SYNC: u8 = 0x05;
-const START: [u8; 3] = [0x16, 0x00, 0x00];
-const ACK: u8 = 0x06;
-const NACK: u8 = 0x15;
+pub(crate) const RESET: [u8; 1] = [0x04];
+pub(crate) const SYNC: u8 = 0x05;
+pub(crate) const START: [u8; 3] = [0x16, 0x00, 0x00];
+pub(crate) const ACK: u8 = 0x06;
+pub(crate) const NACK: u8 = 0x15;
 
-#[derive(Debug, Clone, Copy, PartialEq, TryFromPrimitive)]
+/// The message type of a `Vs2` telegram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u8)]
-enum MessageType {
+pub enum MessageType {
   Request        = 0,
   Response       = 1,
   Unacknowledged = 2,
@@ -43,12 +47,14 @@ struct Header {
   payload_len: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, TryFromPrimitive)]
-#[allow(unused)]
+/// A function code of a `Vs2` telegram, selecting the memory area or bus accessed by a request.
+///
+/// Used with `Protocol::request`; `Protocol::get` and `Protocol::set` always use `VirtualRead` and `VirtualWrite`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[rustfmt::skip]
 #[non_exhaustive]
 #[repr(u8)]
-enum Function {
+pub enum Function {
   VirtualRead               =   1,
   VirtualWrite              =   2,
   PhysicalRead              =   3,
@@ -100,10 +106,61 @@ enum Function {
   GfaWrite                  = 202,
 }
 
+impl Function {
+  /// Returns whether a request with this function sends a payload.
+  pub fn sends_payload(self) -> bool {
+    self == Self::RemoteProcedureCall || !self.returns_payload()
+  }
+
+  /// Returns whether a response to this function returns a payload.
+  pub fn returns_payload(self) -> bool {
+    !matches!(
+      self,
+      Self::VirtualWrite
+        | Self::PhysicalWrite
+        | Self::EepromWrite
+        | Self::VirtualMarketManagerWrite
+        | Self::VirtualWiloWrite
+        | Self::XramWrite
+        | Self::PortWrite
+        | Self::BeWrite
+        | Self::KbusDataelementWrite
+        | Self::KbusDatablockWrite
+        | Self::KbusTransparentWrite
+        | Self::KbusInitializationWrite
+        | Self::KbusEepromLtWrite
+        | Self::KbusControlWrite
+        | Self::KbusMemberlistWrite
+        | Self::KbusVirtualWrite
+        | Self::KbusDirectWrite
+        | Self::KbusIndirectWrite
+        | Self::KbusGatewayWrite
+        | Self::ProcessWrite
+        | Self::OtPhysicalWrite
+        | Self::OtVirtualWrite
+        | Self::GfaWrite
+    )
+  }
+
+  /// Returns whether this function writes to memory other than virtual addresses, e.g. the EEPROM, which only
+  /// supports a limited number of write cycles.
+  pub fn writes_raw(self) -> bool {
+    !self.returns_payload()
+      && !matches!(
+        self,
+        Self::VirtualWrite
+          | Self::VirtualMarketManagerWrite
+          | Self::VirtualWiloWrite
+          | Self::KbusVirtualWrite
+          | Self::OtVirtualWrite
+      )
+  }
+}
+
 #[derive(Debug)]
 pub enum Vs2 {}
 
-fn wrapping_sum<'a>(iter: impl IntoIterator<Item = &'a u8>) -> u8 {
+pub(crate) fn wrapping_sum<'a>(iter: impl IntoIterator<Item = &'a u8>) -> u8 {
   iter.into_iter().fold(0, |acc, &x| acc.wrapping_add(x))
 }
 
@@ -139,94 +196,109 @@ impl Vs2 {
     buffer[checksum_index] = wrapping_sum(&buffer[1..checksum_index]);
     let telegram_len = checksum_index + 1;
 
-    loop {
-      o.write_all(&buffer[..telegram_len]).await?;
-      o.flush().await?;
+    let retries = o.protocol_config().retries;
+
+    let telegram = &buffer[..telegram_len];
+
+    for _ in 0..=retries {
+      write_all(o, telegram).await?;
 
       match Self::read_status(o).await? {
         ACK => return Ok(()),
         NACK => {
           Self::negotiate(o).await?;
         },
-        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "send telegram failed")),
+        status => return Err(ProtocolError::UnexpectedStatus { status, telegram: telegram.to_vec() }.into()),
       }
     }
+
+    Err(ProtocolError::Nack { telegram: telegram.to_vec() }.into())
   }
 
-  async fn read_telegram(o: &mut Optolink, mut payload: Option<&mut [u8]>) -> Result<Header, io::Error> {
+  async fn read_telegram(o: &mut Optolink, mut payload: Option<&mut [u8]>) -> Result<(Header, Vec<u8>), io::Error> {
     log::trace!("Vs2::read_telegram(…)");
 
     const MAX_TELEGRAM_LEN: usize = 1 + 5 + MAX_PAYLOAD_LEN + 1;
     let mut buffer = [0; MAX_TELEGRAM_LEN];
 
-    o.read_exact(&mut buffer[0..1]).await?;
+    read_exact(o, &mut buffer[0..1]).await?;
     if buffer[0] != LEADIN {
-      return Err(io::Error::new(io::ErrorKind::InvalidData, "telegram leadin expected"));
+      return Err(ProtocolError::InvalidLeadin { byte: buffer[0] }.into());
     }
 
-    o.read_exact(&mut buffer[1..2]).await?;
+    read_exact(o, &mut buffer[1..2]).await?;
     let message_len = buffer[1];
 
     let checksum_index = 2 + message_len as usize;
 
-    o.read_exact(&mut buffer[2..(checksum_index + 1)]).await?;
+    read_exact(o, &mut buffer[2..(checksum_index + 1)]).await?;
 
-    let checksum = wrapping_sum(&buffer[1..checksum_index]);
+    let telegram = &buffer[..(checksum_index + 1)];
+    let checksum = wrapping_sum(&telegram[1..checksum_index]);
 
-    if checksum == buffer[checksum_index] {
-      o.write_all(&[ACK]).await?;
-      o.flush().await?;
+    if checksum == telegram[checksum_index] {
+      write_all(o, &[ACK]).await?;
     } else {
-      o.write_all(&[NACK]).await?;
-      o.flush().await?;
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("invalid checksum: {} != {}", checksum, buffer[checksum_index]),
-      ));
+      write_all(o, &[NACK]).await?;
+      return Err(
+        ProtocolError::InvalidChecksum {
+          expected: checksum,
+          actual: telegram[checksum_index],
+          telegram: telegram.to_vec(),
+        }
+        .into(),
+      );
     }
 
-    let message_type = buffer[2];
+    let message_type = telegram[2];
     let message_type = MessageType::try_from(message_type)
-      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "unknown message identifier: {message_type}"))?;
+      .map_err(|_| ProtocolError::UnknownMessageType { message_type, telegram: telegram.to_vec() })?;
 
-    let function = buffer[3];
+    let function = telegram[3];
     let function = Function::try_from(function)
-      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("unknown function: {function}")))?;
+      .map_err(|_| ProtocolError::UnknownFunction { function, telegram: telegram.to_vec() })?;
 
-    let addr = u16::from_be_bytes([buffer[4], buffer[5]]);
+    let addr = u16::from_be_bytes([telegram[4], telegram[5]]);
 
-    let payload_len = buffer[6];
+    let payload_len = telegram[6];
 
     if let Some(ref mut payload) = payload {
-      if payload_len != (message_len - 5) {
-        return Err(io::Error::new(
-          io::ErrorKind::InvalidData,
-          format!("message length ({0}) does not match payload length ({1}): {0} - 5 != {1}", message_len, payload_len),
-        ));
+      if payload_len as usize + 5 != message_len as usize {
+        return Err(
+          ProtocolError::LengthMismatch {
+            expected: payload_len as usize + 5,
+            actual: message_len as usize,
+            telegram: telegram.to_vec(),
+          }
+          .into(),
+        );
       }
 
       if payload.len() != payload_len as usize {
-        return Err(io::Error::new(
-          io::ErrorKind::InvalidData,
-          format!("invalid payload length, expected {}, got {}", payload.len(), payload_len),
-        ));
+        return Err(
+          ProtocolError::LengthMismatch {
+            expected: payload.len(),
+            actual: payload_len as usize,
+            telegram: telegram.to_vec(),
+          }
+          .into(),
+        );
       }
 
-      payload.copy_from_slice(&buffer[7..(7 + payload.len())])
+      payload.copy_from_slice(&telegram[7..(7 + payload.len())])
     } else if message_len != 5 {
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("invalid message length, expected 5, got {}", message_len),
-      ));
+      return Err(
+        ProtocolError::LengthMismatch { expected: 5, actual: message_len as usize, telegram: telegram.to_vec() }.into(),
+      );
     }
 
-    Ok(Header { message_type, function, addr, payload_len })
+    Ok((Header { message_type, function, addr, payload_len }, telegram.to_vec()))
   }
 
   async fn read_status(o: &mut Optolink) -> Result<u8, io::Error> {
     log::trace!("Vs2::read_status(…)");
     let mut status = [0xff];
-    o.read_exact(&mut status).await?;
+    read_exact(o, &mut status).await?;
     Ok(status[0])
   }
 
@@ -234,102 +306,96 @@ impl Vs2 {
     log::trace!("Vs2::reset(…)");
 
     o.purge().await?;
-    o.write_all(&RESET).await?;
-    o.flush().await
+    write_all(o, &RESET).await
   }
 
   pub async fn negotiate(o: &mut Optolink) -> Result<(), io::Error> {
     log::trace!("Vs2::negotiate(…)");
 
-    Self::reset(o).await?;
+    let negotiation_timeout = o.protocol_config().negotiation_timeout;
 
-    loop {
-      if Self::read_status(o).await? != SYNC {
-        continue;
-      }
+    timeout(negotiation_timeout, "VS2 negotiation", async {
+      Self::reset(o).await?;
 
-      o.write_all(&START).await?;
-      o.flush().await?;
+      loop {
+        if Self::read_status(o).await? != SYNC {
+          continue;
+        }
 
-      match Self::read_status(o).await? {
-        ACK => return Ok(()),
-        NACK => continue,
-        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "protocol negotiation failed")),
+        write_all(o, &START).await?;
+
+        match Self::read_status(o).await? {
+          ACK => return Ok(()),
+          NACK => continue,
+          status => return Err(ProtocolError::NegotiationFailed { status }.into()),
+        }
       }
-    }
+    })
+    .await
   }
 
   pub async fn get(o: &mut Optolink, addr: u16, buf: &mut [u8]) -> Result<(), io::Error> {
     log::trace!("Vs2::get(…)");
 
-    let header = Header {
-      message_type: MessageType::Request,
-      function: Function::VirtualRead,
-      addr,
-      payload_len: buf.len().try_into().unwrap(),
-    };
+    Self::request(o, Function::VirtualRead, addr, &[], buf).await
+  }
 
-    Self::write_telegram(o, &header, None).await?;
-    let response_header = Self::read_telegram(o, Some(buf)).await?;
+  pub async fn set(o: &mut Optolink, addr: u16, value: &[u8]) -> Result<(), io::Error> {
+    log::trace!("Vs2::set(…)");
 
-    Self::check_response(&response_header, header.function, addr)?;
+    Self::request(o, Function::VirtualWrite, addr, value, &mut []).await
+  }
 
-    let expected_len = buf.len();
-    let actual_len = response_header.payload_len as usize;
-    if actual_len != expected_len {
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("expected to read {expected_len}, read {actual_len}"),
-      ));
-    }
+  /// Sends a request with the given `function`, sending `payload` if the function
+  /// sends a payload and reading the response payload into `buf` if it returns one.
+  pub async fn request(
+    o: &mut Optolink,
+    function: Function,
+    addr: u16,
+    payload: &[u8],
+    buf: &mut [u8],
+  ) -> Result<(), io::Error> {
+    log::trace!("Vs2::request({function:?}, …)");
 
-    Ok(())
-  }
+    let expected = if function.returns_payload() { buf.len() } else { payload.len() };
 
-  pub async fn set(o: &mut Optolink, addr: u16, value: &[u8]) -> Result<(), io::Error> {
-    log::trace!("Vs2::set(…)");
+    let header =
+      Header { message_type: MessageType::Request, function, addr, payload_len: expected.try_into().unwrap() };
 
-    let header = Header {
-      message_type: MessageType::Request,
-      function: Function::VirtualWrite,
-      addr,
-      payload_len: value.len().try_into().unwrap(),
-    };
+    let payload = function.sends_payload().then_some(payload);
+    Self::write_telegram(o, &header, payload).await?;
 
-    Self::write_telegram(o, &header, Some(value)).await?;
-    let response_header = Self::read_telegram(o, None).await?;
+    let buf = function.returns_payload().then_some(buf);
+    let (response_header, telegram) = Self::read_telegram(o, buf).await?;
 
-    Self::check_response(&response_header, header.function, addr)?;
+    Self::check_response(&response_header, &telegram, header.function, addr)?;
 
-    let expected_len = value.len();
-    let actual_len = response_header.payload_len as usize;
-    if actual_len != expected_len {
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("expected to write {expected_len}, wrote {actual_len}"),
-      ));
+    let actual = response_header.payload_len as usize;
+    if actual != expected {
+      return Err(ProtocolError::LengthMismatch { expected, actual, telegram }.into());
     }
 
     Ok(())
   }
 
-  fn check_response(header: &Header, function: Function, addr: u16) -> Result<(), io::Error> {
+  fn check_response(header: &Header, telegram: &[u8], function: Function, addr: u16) -> Result<(), ProtocolError> {
     if header.message_type != MessageType::Response {
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("expected response message identifier, got {}", header.message_type),
-      ));
+      return Err(ProtocolError::UnexpectedMessageType {
+        message_type: header.message_type as u8,
+        telegram: telegram.to_vec(),
+      });
     }
 
     if header.function != function {
-      return Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        format!("expected function {:?}, got {:?}", function, header.function),
-      ));
+      return Err(ProtocolError::FunctionMismatch {
+        expected: function as u8,
+        actual: header.function as u8,
+        telegram: telegram.to_vec(),
+      });
     }
 
     if header.addr != addr {
-      return Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected address {}, got {}", addr, header.addr)));
+      return Err(ProtocolError::AddressMismatch { expected: addr, actual: header.addr, telegram: telegram.to_vec() });
     }
 
     Ok(())
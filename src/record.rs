@@ -0,0 +1,417 @@
+//! Recording and deterministic replay of Optolink traffic.
+//!
+//! A `Recorder` wraps an `Optolink` and writes every byte sent and received to a `Recording`, one line per read or
+//! write with the time elapsed since the start of the recording, e.g. `0.012345 > 41 05 00 01 00 F8 08 06`. Such a
+//! recording can be decoded using `decode::parse_hex` and played back using `Replay`, turning a failing session into a
+//! regression test.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use vcontrol::{Optolink, VControl, record::{Recording, Replay}};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let file = std::fs::File::create("session.txt")?;
+//! let optolink = Optolink::open("/dev/ttyUSB0").await?.record(file);
+//! let mut vcontrol = VControl::connect(optolink).await?;
+//! vcontrol.get("Gemischte_AT").await?;
+//!
+//! let recording: Recording = std::fs::read_to_string("session.txt")?.parse()?;
+//! let mut vcontrol = VControl::connect(Optolink::from_transport(Replay::new(recording))).await?;
+//! vcontrol.get("Gemischte_AT").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll, ready},
+};
+use std::{collections::VecDeque, fmt, io, str::FromStr, time::Duration};
+
+use tokio::{
+  io::{AsyncRead, AsyncWrite, ReadBuf},
+  sync::mpsc,
+  task,
+  time::{Instant, Sleep, sleep},
+};
+
+use crate::{Error, Optolink, Transport, decode, protocol::Hex};
+
+/// The direction of an `Event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+  /// Bytes sent to the controller, written as `>`.
+  Sent,
+  /// Bytes received from the controller, written as `<`.
+  Received,
+}
+
+impl fmt::Display for Direction {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Sent => ">",
+      Self::Received => "<",
+    }
+    .fmt(f)
+  }
+}
+
+/// Bytes sent or received by a single write or read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+  /// The time elapsed since the start of the recording.
+  pub elapsed: Duration,
+  /// The direction of the bytes.
+  pub direction: Direction,
+  /// The bytes.
+  pub bytes: Vec<u8>,
+}
+
+impl fmt::Display for Event {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:.6} {} {}", self.elapsed.as_secs_f64(), self.direction, Hex(&self.bytes))
+  }
+}
+
+/// A recording of Optolink traffic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+  /// The recorded events in chronological order.
+  pub events: Vec<Event>,
+}
+
+impl fmt::Display for Recording {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for event in &self.events {
+      writeln!(f, "{event}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl FromStr for Recording {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let events = decode::parse_hex(s)?
+      .into_iter()
+      .map(|chunk| {
+        let timestamp = chunk.timestamp.unwrap_or_default();
+
+        let (elapsed, direction) = match timestamp.split_once(' ') {
+          Some((elapsed, ">")) => (elapsed, Direction::Sent),
+          Some((elapsed, "<")) => (elapsed, Direction::Received),
+          _ => return Err(Error::InvalidFormat(format!("expected elapsed time and direction, got “{timestamp}”"))),
+        };
+
+        let elapsed = elapsed
+          .parse()
+          .ok()
+          .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+          .ok_or_else(|| Error::InvalidFormat(format!("invalid elapsed time “{elapsed}”")))?;
+
+        Ok(Event { elapsed, direction, bytes: chunk.bytes })
+      })
+      .collect::<Result<_, _>>()?;
+
+    Ok(Self { events })
+  }
+}
+
+/// A `Transport` which records all bytes sent and received on an `Optolink` to a writer, see [`Optolink::record`].
+///
+/// Each read or write is sent to a blocking task which writes it to the recording immediately, so that reads and
+/// writes are not delayed by the recording and the recording is complete even if the session ends abruptly.
+/// Failing to write the recording does not affect the connection.
+///
+/// The blocking task is only started by the first read or write, so a `Recorder` can be created outside of a Tokio
+/// runtime.
+pub struct Recorder {
+  optolink: Optolink,
+  events: mpsc::UnboundedSender<Event>,
+  writer: Option<Box<dyn FnOnce() + Send>>,
+  start: Instant,
+}
+
+impl fmt::Debug for Recorder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Recorder").field("optolink", &self.optolink).field("start", &self.start).finish_non_exhaustive()
+  }
+}
+
+impl Recorder {
+  /// Creates a new `Recorder`, starting the recording now.
+  pub fn new<W>(optolink: Optolink, mut writer: W) -> Self
+  where
+    W: io::Write + Send + 'static,
+  {
+    let (events, mut receiver) = mpsc::unbounded_channel::<Event>();
+
+    let writer = Box::new(move || {
+      task::spawn_blocking(move || {
+        while let Some(event) = receiver.blocking_recv() {
+          if let Err(err) = writeln!(writer, "{event}").and_then(|()| writer.flush()) {
+            log::warn!("Failed to record “{event}”: {err}");
+          }
+        }
+      });
+    });
+
+    Self { optolink, events, writer: Some(writer), start: Instant::now() }
+  }
+
+  fn record(&mut self, direction: Direction, bytes: &[u8]) {
+    if bytes.is_empty() {
+      return;
+    }
+
+    if let Some(spawn_writer) = self.writer.take() {
+      spawn_writer();
+    }
+
+    let event = Event { elapsed: self.start.elapsed(), direction, bytes: bytes.to_vec() };
+
+    if let Err(mpsc::error::SendError(event)) = self.events.send(event) {
+      log::warn!("Failed to record “{event}”: recording stopped");
+    }
+  }
+}
+
+impl AsyncRead for Recorder {
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+    let filled = buf.filled().len();
+
+    ready!(Pin::new(&mut self.optolink).poll_read(cx, buf))?;
+    self.record(Direction::Received, &buf.filled()[filled..]);
+
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl AsyncWrite for Recorder {
+  fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    let n = ready!(Pin::new(&mut self.optolink).poll_write(cx, buf))?;
+    self.record(Direction::Sent, &buf[..n]);
+
+    Poll::Ready(Ok(n))
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.optolink).poll_flush(cx)
+  }
+
+  fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.optolink).poll_shutdown(cx)
+  }
+}
+
+impl Transport for Recorder {
+  fn purge(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+    Box::pin(self.optolink.purge())
+  }
+
+  fn reinitialize(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+    Box::pin(self.optolink.reinitialize())
+  }
+}
+
+/// A `Transport` which plays back a `Recording`.
+///
+/// Writes must match the recorded bytes sent, otherwise they fail with an `io::ErrorKind::InvalidData` error. Recorded
+/// bytes received are returned by reads after the same delay as in the recording, so that timing-dependent behaviour
+/// is reproduced as well. While the next recorded event is a write, reads wait indefinitely, like for a controller
+/// which does not respond, until the read times out. Reads return end of file once the recording is exhausted.
+///
+/// Purging does nothing, since purged bytes are never read and therefore not recorded.
+#[derive(Debug)]
+pub struct Replay {
+  events: VecDeque<Event>,
+  elapsed: Duration,
+  delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Replay {
+  /// Creates a new `Replay` of the given recording.
+  pub fn new(recording: Recording) -> Self {
+    Self { events: recording.events.into(), elapsed: Duration::ZERO, delay: None }
+  }
+}
+
+impl AsyncRead for Replay {
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+    let this = &mut *self;
+
+    let Some(event) = this.events.front_mut() else {
+      return Poll::Ready(Ok(()));
+    };
+
+    if event.direction == Direction::Sent {
+      return Poll::Pending;
+    }
+
+    if event.elapsed > this.elapsed {
+      let delay = this.delay.get_or_insert_with(|| Box::pin(sleep(event.elapsed - this.elapsed)));
+      ready!(delay.as_mut().poll(cx));
+
+      this.delay = None;
+      this.elapsed = event.elapsed;
+    }
+
+    let n = buf.remaining().min(event.bytes.len());
+    buf.put_slice(&event.bytes[..n]);
+    event.bytes.drain(..n);
+
+    if event.bytes.is_empty() {
+      this.events.pop_front();
+    }
+
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl AsyncWrite for Replay {
+  fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    let this = &mut *self;
+
+    let unexpected = |expected: String| {
+      Poll::Ready(Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected write of {}, {expected}", Hex(buf)),
+      )))
+    };
+
+    let Some(event) = this.events.front_mut() else {
+      return unexpected("expected end of recording".into());
+    };
+
+    if event.direction == Direction::Received {
+      return unexpected(format!("expected read of {}", Hex(&event.bytes)));
+    }
+
+    let n = buf.len().min(event.bytes.len());
+    if buf[..n] != event.bytes[..n] {
+      return unexpected(format!("expected {}", Hex(&event.bytes)));
+    }
+
+    event.bytes.drain(..n);
+    this.elapsed = event.elapsed;
+
+    if event.bytes.is_empty() {
+      this.events.pop_front();
+    }
+
+    Poll::Ready(Ok(n))
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl Transport for Replay {
+  fn purge(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+    Box::pin(async { Ok(()) })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+
+  use tokio::sync::oneshot;
+
+  use super::*;
+  use crate::{
+    Protocol, VControl, Value,
+    sim::{Simulator, connect, vscot_ho1},
+  };
+
+  /// A writer which signals `done` once it is dropped by the recording task.
+  struct Shared {
+    buf: Arc<Mutex<Vec<u8>>>,
+    done: Option<oneshot::Sender<()>>,
+  }
+
+  impl io::Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.buf.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  impl Drop for Shared {
+    fn drop(&mut self) {
+      if let Some(done) = self.done.take() {
+        let _ = done.send(());
+      }
+    }
+  }
+
+  #[test]
+  fn parse_recording() {
+    let recording: Recording = "0.000000 > 04\n0.500000 < 05 # sync\n0.501000 > 01 F7 00 F8 02\n".parse().unwrap();
+
+    assert_eq!(recording.events.len(), 3);
+    assert_eq!(
+      recording.events[1],
+      Event { elapsed: Duration::from_millis(500), direction: Direction::Received, bytes: vec![0x05] }
+    );
+    assert_eq!(recording.to_string().lines().nth(2), Some("0.501000 > 01 F7 00 F8 02"));
+
+    assert!("12:00 04".parse::<Recording>().is_err());
+    assert!("0.1 ? 04".parse::<Recording>().is_err());
+  }
+
+  #[tokio::test]
+  async fn record_and_replay() {
+    let simulator = Simulator::from_device(vscot_ho1());
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+
+    let buf = Arc::new(Mutex::new(Vec::new()));
+    let (done, finished) = oneshot::channel();
+    let writer = Shared { buf: buf.clone(), done: Some(done) };
+    let mut vcontrol = VControl::connect(connect(simulator).await.record(writer)).await.unwrap();
+    assert_eq!(vcontrol.get("TiefpassTemperaturwert_ATS").await.unwrap().value, Value::Double(-3.5));
+    drop(vcontrol);
+
+    // Wait for the recording task to finish writing.
+    finished.await.unwrap();
+
+    let recording: Recording = String::from_utf8(buf.lock().unwrap().clone()).unwrap().parse().unwrap();
+    assert!(recording.events.iter().any(|event| event.direction == Direction::Sent));
+    assert!(recording.events.iter().any(|event| event.direction == Direction::Received));
+
+    let mut vcontrol = VControl::connect(Optolink::from_transport(Replay::new(recording))).await.unwrap();
+    assert_eq!(vcontrol.protocol(), Protocol::Vs2);
+    assert_eq!(vcontrol.get("TiefpassTemperaturwert_ATS").await.unwrap().value, Value::Double(-3.5));
+
+    // A session diverging from the recording fails.
+    assert!(vcontrol.get("TiefpassTemperaturwert_ATS").await.is_err());
+  }
+
+  #[test]
+  fn record_outside_runtime() {
+    let optolink = Optolink::from_transport(Replay::new(Recording::default())).record(io::sink());
+    drop(optolink);
+  }
+
+  #[tokio::test]
+  async fn replay_vs1_fixture() {
+    let recording: Recording = include_str!("record/vs1.txt").parse().unwrap();
+
+    let mut vcontrol = VControl::connect(Optolink::from_transport(Replay::new(recording))).await.unwrap();
+    assert_eq!(vcontrol.protocol(), Protocol::Vs1);
+    assert_eq!(vcontrol.device().name(), vscot_ho1().name());
+    assert_eq!(vcontrol.get("TiefpassTemperaturwert_ATS").await.unwrap().value, Value::Double(-3.5));
+  }
+}
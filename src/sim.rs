@@ -0,0 +1,712 @@
+use std::{
+  fmt, io, mem,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use tokio::{
+  io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+  net::TcpListener,
+  time::sleep,
+};
+
+use crate::{
+  DataType, Device, Protocol,
+  commands::system,
+  device::DEVICES,
+  protocol::{gwg, vs1, vs2},
+  types::{DateTime, DeviceId, DeviceIdF0},
+};
+
+const MEMORY_LEN: usize = 0x10000;
+
+/// A simulated heating controller.
+///
+/// The simulator answers `Vs1` and `Vs2` requests on any stream using its own memory, which is shared
+/// by the virtual, GFA and process functions, so that `Optolink`, `Protocol` and `VControl` can be tested without a real device.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio::net::TcpListener;
+/// use vcontrol::{Optolink, VControl, sim::Simulator};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+/// let addr = listener.local_addr()?;
+///
+/// let simulator = Simulator::from_scan_cache(&std::fs::read("scan-cache.bin")?);
+/// tokio::spawn(async move { simulator.listen(listener).await });
+///
+/// let vcontrol = VControl::connect(Optolink::connect(addr).await?).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Simulator {
+  memory: Arc<Mutex<Box<[u8]>>>,
+  protocol: Protocol,
+  sync_interval: Duration,
+  max_read_len: usize,
+}
+
+impl fmt::Debug for Simulator {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Simulator")
+      .field("protocol", &self.protocol)
+      .field("sync_interval", &self.sync_interval)
+      .field("max_read_len", &self.max_read_len)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Default for Simulator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Simulator {
+  /// Creates a simulator with empty memory, i.e. all bytes set to `0xFF`.
+  pub fn new() -> Self {
+    Self {
+      memory: Arc::new(Mutex::new(vec![0xff; MEMORY_LEN].into_boxed_slice())),
+      protocol: Protocol::Vs2,
+      sync_interval: Duration::from_secs(2),
+      max_read_len: usize::MAX,
+    }
+  }
+
+  /// Creates a simulator with its memory seeded from a `scan-cache.bin` dump.
+  pub fn from_scan_cache(bytes: &[u8]) -> Self {
+    let simulator = Self::new();
+    simulator.write(0, &bytes[..bytes.len().min(MEMORY_LEN)]);
+    simulator
+  }
+
+  /// Creates a simulator which identifies as the given `Device`.
+  ///
+  /// The values of all device commands are set to zero, dates are set to 2000-01-01.
+  pub fn from_device(device: &'static Device) -> Self {
+    let simulator = Self::new();
+
+    for command in device.commands().values() {
+      simulator.write(command.addr, &vec![0; command.block_len]);
+    }
+
+    let date_time = DateTime::new(2000, 1, 1, 0, 0, 0).unwrap().to_bytes();
+    for command in device.commands().values() {
+      if matches!(command.data_type, DataType::Date | DataType::DateTime)
+        && command.byte_len == date_time.len()
+        && command.byte_pos + command.byte_len <= command.block_len
+      {
+        simulator.write(command.addr + command.byte_pos as u16, &date_time);
+      }
+    }
+
+    // Find an identifier which is detected as this exact device.
+    let device_id = DEVICES
+      .entries()
+      .filter(|(_, d)| d.name() == device.name())
+      .flat_map(|(id_range, _)| {
+        let software_index = id_range.software_index.unwrap_or(0);
+        let software_index_till = id_range.software_index_till.unwrap_or(software_index);
+
+        (software_index..=software_index_till).map(move |software_index| {
+          let device_id = DeviceId {
+            group_id: id_range.group_id,
+            id: id_range.id,
+            hardware_index: id_range.hardware_index.unwrap_or(0),
+            software_index,
+            protocol_version_lda: 0,
+            protocol_version_rda: 0,
+            developer_version: 0,
+          };
+
+          (device_id, id_range.f0.map(DeviceIdF0))
+        })
+      })
+      .find(|&(device_id, device_id_f0)| {
+        Device::detect(device_id, device_id_f0).is_some_and(|d| d.name() == device.name())
+      });
+
+    if let Some((device_id, device_id_f0)) = device_id {
+      simulator.write(system::DEVICE_ID.addr, &device_id.to_bytes());
+
+      if let Some(device_id_f0) = device_id_f0 {
+        simulator.write(system::DEVICE_ID_F0.addr, &device_id_f0.0.to_be_bytes());
+      }
+    } else {
+      log::warn!("Device {} cannot be detected by its identifier.", device.name());
+    }
+
+    simulator
+  }
+
+  /// Sets the most recent protocol supported by the simulated controller.
+  ///
+  /// A controller supporting `Vs2` also supports `Vs1`, a controller supporting `Gwg` only supports `Gwg`.
+  pub fn protocol(mut self, protocol: Protocol) -> Self {
+    self.protocol = protocol;
+    self
+  }
+
+  /// Sets the interval in which `SYNC` is sent while waiting for a `Vs1` request.
+  pub fn sync_interval(mut self, sync_interval: Duration) -> Self {
+    self.sync_interval = sync_interval;
+    self
+  }
+
+  /// Sets the maximum length of a read request, longer requests are not answered like by some real controllers.
+  pub fn max_read_len(mut self, max_read_len: usize) -> Self {
+    self.max_read_len = max_read_len;
+    self
+  }
+
+  /// Reads the memory starting at address `addr` into `buf`.
+  ///
+  /// Bytes past the end of the address space are read as `0xFF`.
+  pub fn read(&self, addr: u16, buf: &mut [u8]) {
+    let memory = self.memory.lock().unwrap();
+
+    let start = addr as usize;
+    let end = (start + buf.len()).min(MEMORY_LEN);
+    buf.fill(0xff);
+    buf[..(end - start)].copy_from_slice(&memory[start..end]);
+  }
+
+  /// Writes `bytes` to the memory starting at address `addr`.
+  ///
+  /// Bytes past the end of the address space are ignored.
+  pub fn write(&self, addr: u16, bytes: &[u8]) {
+    let mut memory = self.memory.lock().unwrap();
+
+    let start = addr as usize;
+    let end = (start + bytes.len()).min(MEMORY_LEN);
+    memory[start..end].copy_from_slice(&bytes[..(end - start)]);
+  }
+
+  /// The protocol of a new session or after a reset, which is `Vs1` unless only `Gwg` is supported.
+  fn initial_protocol(&self) -> Protocol {
+    match self.protocol {
+      Protocol::Gwg => Protocol::Gwg,
+      Protocol::Vs1 | Protocol::Vs2 => Protocol::Vs1,
+    }
+  }
+
+  /// Accepts connections on the given listener and serves them one at a time.
+  pub async fn listen(&self, listener: TcpListener) -> io::Result<()> {
+    log::trace!("Simulator::listen(…)");
+
+    loop {
+      let (stream, addr) = listener.accept().await?;
+      log::debug!("Simulator accepted connection from {addr}.");
+
+      if let Err(err) = self.serve(stream).await {
+        log::warn!("Simulator connection from {addr} failed: {err}");
+      }
+    }
+  }
+
+  /// Serves a single connection until the stream is closed.
+  pub async fn serve<S>(&self, stream: S) -> io::Result<()>
+  where
+    S: AsyncRead + AsyncWrite + Unpin,
+  {
+    log::trace!("Simulator::serve(…)");
+
+    let protocol = self.initial_protocol();
+    let mut session = Session { simulator: self, stream, protocol, buf: Vec::new(), synced: false };
+
+    loop {
+      let byte = if session.is_idle() {
+        tokio::select! {
+          byte = session.stream.read_u8() => byte,
+          _ = sleep(self.sync_interval) => {
+            session.sync().await?;
+            continue;
+          },
+        }
+      } else {
+        session.stream.read_u8().await
+      };
+
+      match byte {
+        Ok(byte) => session.receive(byte).await?,
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        Err(err) => return Err(err),
+      }
+    }
+  }
+}
+
+/// State of a single connection to a `Simulator`.
+struct Session<'s, S> {
+  simulator: &'s Simulator,
+  stream: S,
+  protocol: Protocol,
+  buf: Vec<u8>,
+  synced: bool,
+}
+
+impl<S> Session<'_, S>
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+{
+  fn is_idle(&self) -> bool {
+    matches!(self.protocol, Protocol::Vs1 | Protocol::Gwg) && self.buf.is_empty()
+  }
+
+  async fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+    self.stream.write_all(bytes).await?;
+    self.stream.flush().await
+  }
+
+  async fn sync(&mut self) -> io::Result<()> {
+    log::trace!("Simulator::sync(…)");
+
+    self.synced = true;
+    self.send(&[vs1::SYNC]).await
+  }
+
+  async fn receive(&mut self, byte: u8) -> io::Result<()> {
+    if self.buf.is_empty() && byte == vs1::RESET {
+      log::trace!("Simulator::receive(…): reset");
+
+      self.protocol = self.simulator.initial_protocol();
+
+      // Only send `SYNC` if the previous one was consumed by a request,
+      // otherwise the client could mistake a stale `SYNC` for a response.
+      if !self.synced {
+        self.sync().await?;
+      }
+
+      return Ok(());
+    }
+
+    self.buf.push(byte);
+
+    match self.protocol {
+      Protocol::Vs1 => self.receive_vs1().await,
+      Protocol::Vs2 => self.receive_vs2().await,
+      Protocol::Gwg => self.receive_gwg().await,
+    }
+  }
+
+  async fn receive_gwg(&mut self) -> io::Result<()> {
+    const READ: u8 = gwg::Function::VirtualRead as u8;
+    const WRITE: u8 = gwg::Function::VirtualWrite as u8;
+    const PHYSICAL_READ: u8 = gwg::Function::PhysicalRead as u8;
+    const PHYSICAL_WRITE: u8 = gwg::Function::PhysicalWrite as u8;
+
+    match self.buf[..] {
+      [0x01, READ | PHYSICAL_READ, addr, len, gwg::EOT] => {
+        let mut value = vec![0; len as usize];
+        self.simulator.read(addr.into(), &mut value);
+
+        self.buf.clear();
+        self.synced = false;
+        self.send(&value).await
+      },
+      [0x01, WRITE | PHYSICAL_WRITE, addr, len, ref value @ ..] if value.len() == len as usize => {
+        self.simulator.write(addr.into(), value);
+
+        self.buf.clear();
+        self.synced = false;
+        self.send(&[0x00]).await
+      },
+      [0x01] | [0x01, READ | WRITE | PHYSICAL_READ | PHYSICAL_WRITE, ..] if self.buf.len() < 5 => Ok(()),
+      [0x01, WRITE | PHYSICAL_WRITE, ..] => Ok(()),
+      _ => {
+        self.buf.clear();
+        Ok(())
+      },
+    }
+  }
+
+  async fn receive_vs1(&mut self) -> io::Result<()> {
+    const READ: u8 = vs1::Function::VirtualRead as u8;
+    const WRITE: u8 = vs1::Function::VirtualWrite as u8;
+    const GFA_READ: u8 = vs1::Function::GfaRead as u8;
+    const GFA_WRITE: u8 = vs1::Function::GfaWrite as u8;
+    const PROCESS_READ: u8 = vs1::Function::ProcessRead as u8;
+    const PROCESS_WRITE: u8 = vs1::Function::ProcessWrite as u8;
+
+    match self.buf[..] {
+      [0x01, READ | GFA_READ | PROCESS_READ, _, _, len] if len as usize > self.simulator.max_read_len => {
+        log::debug!("Simulator::receive_vs1(…): ignoring read of {len} bytes");
+
+        self.buf.clear();
+        self.synced = false;
+        Ok(())
+      },
+      [0x01, READ | GFA_READ | PROCESS_READ, addr_hi, addr_lo, len] => {
+        let mut value = vec![0; len as usize];
+        self.simulator.read(u16::from_be_bytes([addr_hi, addr_lo]), &mut value);
+
+        self.buf.clear();
+        self.synced = false;
+        self.send(&value).await
+      },
+      [0x01, WRITE | GFA_WRITE | PROCESS_WRITE, addr_hi, addr_lo, len, ref value @ ..]
+        if value.len() == len as usize =>
+      {
+        self.simulator.write(u16::from_be_bytes([addr_hi, addr_lo]), value);
+
+        self.buf.clear();
+        self.synced = false;
+        self.send(&[0x00]).await
+      },
+      [0x01] | [0x01, READ | WRITE | GFA_READ | GFA_WRITE | PROCESS_READ | PROCESS_WRITE, ..] => Ok(()),
+      ref buf if matches!(self.simulator.protocol, Protocol::Vs2) && vs2::START.starts_with(buf) => {
+        if buf.len() == vs2::START.len() {
+          log::trace!("Simulator::receive_vs1(…): switching to VS2");
+
+          self.buf.clear();
+          self.protocol = Protocol::Vs2;
+          self.send(&[vs2::ACK]).await?;
+        }
+
+        Ok(())
+      },
+      _ => {
+        self.buf.clear();
+        Ok(())
+      },
+    }
+  }
+
+  async fn receive_vs2(&mut self) -> io::Result<()> {
+    if self.buf[0] != vs2::LEADIN {
+      if vs2::START.starts_with(&self.buf) {
+        if self.buf.len() == vs2::START.len() {
+          self.buf.clear();
+          self.send(&[vs2::ACK]).await?;
+        }
+      } else {
+        // Acknowledgements for responses and stray bytes.
+        self.buf.clear();
+      }
+
+      return Ok(());
+    }
+
+    let Some(&message_len) = self.buf.get(1) else { return Ok(()) };
+    let checksum_index = 2 + message_len as usize;
+    if self.buf.len() <= checksum_index {
+      return Ok(());
+    }
+
+    let telegram = mem::take(&mut self.buf);
+
+    if vs2::wrapping_sum(&telegram[1..checksum_index]) != telegram[checksum_index] {
+      log::debug!("Simulator::receive_vs2(…): invalid checksum");
+      return self.send(&[vs2::NACK]).await;
+    }
+
+    self.send(&[vs2::ACK]).await?;
+
+    let [message_type, function, addr_hi, addr_lo, payload_len, ref payload @ ..] = telegram[2..checksum_index] else {
+      log::debug!("Simulator::receive_vs2(…): message too short");
+      return Ok(());
+    };
+    let addr = u16::from_be_bytes([addr_hi, addr_lo]);
+
+    let request = vs2::MessageType::try_from(message_type) == Ok(vs2::MessageType::Request);
+    let response = match vs2::Function::try_from(function) {
+      Ok(vs2::Function::VirtualRead | vs2::Function::GfaRead | vs2::Function::ProcessRead)
+        if request && payload_len as usize > self.simulator.max_read_len =>
+      {
+        log::debug!("Simulator::receive_vs2(…): ignoring read of {payload_len} bytes");
+        return Ok(());
+      },
+      Ok(vs2::Function::VirtualRead | vs2::Function::GfaRead | vs2::Function::ProcessRead) if request => {
+        let mut value = vec![0; payload_len as usize];
+        self.simulator.read(addr, &mut value);
+        telegram_vs2(vs2::MessageType::Response, function, addr, payload_len, &value)
+      },
+      Ok(vs2::Function::VirtualWrite | vs2::Function::GfaWrite | vs2::Function::ProcessWrite)
+        if request && payload.len() == payload_len as usize =>
+      {
+        self.simulator.write(addr, payload);
+        telegram_vs2(vs2::MessageType::Response, function, addr, payload_len, &[])
+      },
+      _ => telegram_vs2(vs2::MessageType::Error, function, addr, payload_len, &[]),
+    };
+
+    self.send(&response).await
+  }
+}
+
+fn telegram_vs2(message_type: vs2::MessageType, function: u8, addr: u16, payload_len: u8, payload: &[u8]) -> Vec<u8> {
+  let mut telegram = vec![vs2::LEADIN, 5 + payload.len() as u8, message_type as u8, function];
+  telegram.extend(addr.to_be_bytes());
+  telegram.push(payload_len);
+  telegram.extend(payload);
+  telegram.push(vs2::wrapping_sum(&telegram[1..]));
+  telegram
+}
+
+/// Returns the `VScotHO1_4` device used throughout the tests.
+#[cfg(test)]
+pub(crate) fn vscot_ho1() -> &'static Device {
+  let device_id = DeviceId::from_bytes(&[0x20, 0xCB, 0x00, 0x08, 0x00, 0x00, 0x01, 0x46]);
+  Device::detect(device_id, None).unwrap()
+}
+
+/// Returns an `Optolink` connected to the given simulator.
+#[cfg(test)]
+pub(crate) async fn connect(simulator: Simulator) -> crate::Optolink {
+  let (stream, server) = tokio::io::duplex(256);
+  tokio::spawn(async move { simulator.serve(server).await });
+
+  crate::Optolink::from_stream(stream)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::{Error, Function, Optolink, ProtocolConfig, VControl, Value};
+
+  #[tokio::test]
+  async fn detect_vs2() {
+    let mut optolink = connect(Simulator::new()).await;
+    assert!(matches!(Protocol::detect(&mut optolink).await, Some(Protocol::Vs2)));
+  }
+
+  #[tokio::test]
+  async fn detect_vs1() {
+    let simulator = Simulator::new().protocol(Protocol::Vs1).sync_interval(Duration::from_millis(100));
+    let mut optolink = connect(simulator).await;
+    assert!(matches!(Protocol::detect(&mut optolink).await, Some(Protocol::Vs1)));
+  }
+
+  #[tokio::test]
+  async fn detect_gwg() {
+    let simulator =
+      Simulator::from_device(vscot_ho1()).protocol(Protocol::Gwg).sync_interval(Duration::from_millis(100));
+    let mut optolink = connect(simulator).await;
+    assert!(matches!(Protocol::detect(&mut optolink).await, Some(Protocol::Gwg)));
+  }
+
+  async fn get_and_set(simulator: Simulator, protocol: Protocol) {
+    let device = vscot_ho1();
+
+    // `TiefpassTemperaturwert_ATS`
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+
+    let mut vcontrol = VControl::connect(connect(simulator.clone()).await).await.unwrap();
+    assert_eq!(vcontrol.device().name(), device.name());
+    assert_eq!(vcontrol.protocol().to_string(), protocol.to_string());
+
+    let value = vcontrol.get("TiefpassTemperaturwert_ATS").await.unwrap();
+    assert_eq!(value.value, Value::Double(-3.5));
+
+    vcontrol.set("KD3_KonfiNeigungA1", Value::Double(1.5)).await.unwrap();
+    let mut buf = [0];
+    simulator.read(0x27D3, &mut buf);
+    assert_eq!(buf, [15]);
+
+    let value = vcontrol.get("KD3_KonfiNeigungA1").await.unwrap();
+    assert_eq!(value.value, Value::Double(1.5));
+  }
+
+  #[tokio::test]
+  async fn vcontrol_vs2() {
+    get_and_set(Simulator::from_device(vscot_ho1()), Protocol::Vs2).await;
+  }
+
+  #[tokio::test]
+  async fn vcontrol_vs1() {
+    let simulator =
+      Simulator::from_device(vscot_ho1()).protocol(Protocol::Vs1).sync_interval(Duration::from_millis(100));
+    get_and_set(simulator, Protocol::Vs1).await;
+  }
+
+  #[tokio::test]
+  async fn vs1_gfa_and_process() {
+    let simulator = Simulator::new().protocol(Protocol::Vs1).sync_interval(Duration::from_millis(100));
+    simulator.write(0x0800, &[0x12, 0x34]);
+    let mut optolink = connect(simulator.clone()).await;
+    optolink.set_protocol_config(ProtocolConfig { allow_raw_writes: true, ..optolink.protocol_config() });
+
+    let protocol = Protocol::Vs1;
+
+    let mut buf = [0; 2];
+    protocol.request(&mut optolink, Function::GfaRead, 0x0800, &[], &mut buf).await.unwrap();
+    assert_eq!(buf, [0x12, 0x34]);
+
+    protocol.request(&mut optolink, Function::ProcessWrite, 0x0801, &[0x56], &mut []).await.unwrap();
+    protocol.request(&mut optolink, Function::ProcessRead, 0x0800, &[], &mut buf).await.unwrap();
+    assert_eq!(buf, [0x12, 0x56]);
+  }
+
+  #[tokio::test]
+  async fn vcontrol_gwg() {
+    let simulator =
+      Simulator::from_device(vscot_ho1()).protocol(Protocol::Gwg).sync_interval(Duration::from_millis(100));
+    simulator.write(0x0042, &[0x12]);
+
+    let mut optolink = connect(simulator.clone()).await;
+    optolink.set_protocol_config(ProtocolConfig {
+      read_timeout: Duration::from_millis(300),
+      negotiation_timeout: Duration::from_millis(500),
+      ..ProtocolConfig::default()
+    });
+
+    let mut vcontrol = VControl::connect(optolink).await.unwrap();
+    assert!(matches!(vcontrol.protocol(), Protocol::Gwg));
+    assert_eq!(vcontrol.device().name(), vscot_ho1().name());
+
+    let mut buf = [0];
+    vcontrol.request(Function::PhysicalRead, 0x0042, &[], &mut buf).await.unwrap();
+    assert_eq!(buf, [0x12]);
+
+    vcontrol.request(Function::VirtualWrite, 0x0043, &[0x34], &mut []).await.unwrap();
+    simulator.read(0x0043, &mut buf);
+    assert_eq!(buf, [0x34]);
+
+    assert!(vcontrol.request(Function::VirtualRead, 0x0100, &[], &mut buf).await.is_err());
+  }
+
+  #[tokio::test]
+  #[ignore = "`build/event_types.used.json` contains no default values until it is regenerated"]
+  async fn vcontrol_reset_to_default() {
+    let device = vscot_ho1();
+    let mut vcontrol = VControl::connect(connect(Simulator::from_device(device)).await).await.unwrap();
+
+    let mut commands = device
+      .commands()
+      .entries()
+      .filter(|(_, command)| command.access_mode().is_read() && command.access_mode().is_write())
+      .filter_map(|(name, command)| Some((*name, command.default_value()?)))
+      .collect::<Vec<_>>();
+    commands.sort_by_key(|(name, _)| *name);
+    assert!(!commands.is_empty(), "no writable command with a default value");
+
+    for (name, default_value) in commands {
+      if vcontrol.get(name).await.unwrap().value == default_value {
+        continue;
+      }
+
+      vcontrol.reset_to_default(name).await.unwrap();
+      assert_eq!(vcontrol.get(name).await.unwrap().value, default_value);
+      return;
+    }
+
+    panic!("all commands already have their default value");
+  }
+
+  #[tokio::test]
+  async fn vcontrol_set_mapping_label() {
+    let simulator = Simulator::from_device(vscot_ho1());
+    let mut vcontrol = VControl::connect(connect(simulator.clone()).await).await.unwrap();
+
+    vcontrol.set("BedienteilBA_GWGA1", Value::String("heating + dhw".into())).await.unwrap();
+    let mut buf = [0];
+    simulator.read(0x2323, &mut buf);
+    assert_eq!(buf, [2]);
+
+    let value = vcontrol.get("BedienteilBA_GWGA1").await.unwrap();
+    assert_eq!(value.to_string(), "Heating + DHW");
+
+    match vcontrol.set("BedienteilBA_GWGA1", Value::String("Heating+DHW".into())).await {
+      Err(Error::UnknownEnumVariant(message)) => {
+        assert!(message.contains("did you mean “Heating + DHW”?"), "{message}");
+        assert!(message.contains("Standby, Only DHW, Heating + DHW"), "{message}");
+      },
+      res => panic!("expected unknown variant, got {res:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn vcontrol_get_many() {
+    // Batches must not exceed `ProtocolConfig::max_batch_len`.
+    let simulator = Simulator::from_device(vscot_ho1()).max_read_len(ProtocolConfig::default().max_batch_len);
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+
+    let mut vcontrol = VControl::connect(connect(simulator).await).await.unwrap();
+
+    let mut commands = vscot_ho1()
+      .commands()
+      .entries()
+      .filter(|(_, command)| command.access_mode().is_read())
+      .map(|(name, _)| *name)
+      .collect::<Vec<_>>();
+    commands.push("Unbekannt");
+
+    let values = vcontrol.get_many(&commands).await.unwrap();
+    assert_eq!(values.len(), commands.len());
+
+    for (command, value) in commands.iter().zip(values) {
+      match vcontrol.get(command).await {
+        Ok(expected) => assert_eq!(value.unwrap().value, expected.value, "{command}"),
+        Err(_) => assert!(value.is_err(), "{command}"),
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn vcontrol_get_many_failed_batch() {
+    let simulator = Simulator::from_device(vscot_ho1()).max_read_len(8).sync_interval(Duration::from_millis(50));
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+
+    let mut optolink = connect(simulator).await;
+    optolink
+      .set_protocol_config(ProtocolConfig { read_timeout: Duration::from_millis(200), ..ProtocolConfig::default() });
+    let mut vcontrol = VControl::connect(optolink).await.unwrap();
+
+    let (long_command, _) = vscot_ho1()
+      .commands()
+      .entries()
+      .find(|(_, command)| {
+        command.access_mode().is_read()
+          && command.read_function == Function::VirtualRead
+          && command.block_len > 8
+          && command.addr.abs_diff(0x5525) > 0x100
+      })
+      .unwrap();
+
+    let values = vcontrol.get_many(&[long_command, "TiefpassTemperaturwert_ATS"]).await.unwrap();
+    assert!(matches!(values[0], Err(Error::Timeout(_))), "{:?}", values[0]);
+    assert_eq!(values[1].as_ref().unwrap().value, Value::Double(-3.5));
+  }
+
+  #[tokio::test]
+  async fn from_scan_cache() {
+    let mut scan_cache = vec![0xff; 0x100];
+    scan_cache[0xF8..].copy_from_slice(&[0x20, 0xCB, 0x00, 0x08, 0x00, 0x00, 0x01, 0x46]);
+
+    let simulator = Simulator::from_scan_cache(&scan_cache);
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { simulator.listen(listener).await });
+
+    let vcontrol = VControl::connect(Optolink::connect(addr).await.unwrap()).await.unwrap();
+    assert_eq!(vcontrol.device().name(), vscot_ho1().name());
+  }
+
+  #[tokio::test]
+  async fn vs2_invalid_checksum() {
+    let (mut client, server) = tokio::io::duplex(64);
+    tokio::spawn(async move { Simulator::new().serve(server).await });
+
+    client.write_all(&[vs2::RESET[0]]).await.unwrap();
+    assert_eq!(client.read_u8().await.unwrap(), vs2::SYNC);
+    client.write_all(&vs2::START).await.unwrap();
+    assert_eq!(client.read_u8().await.unwrap(), vs2::ACK);
+
+    client.write_all(&[vs2::LEADIN, 0x05, 0x00, 0x01, 0x00, 0xF8, 0x08, 0x00]).await.unwrap();
+    assert_eq!(client.read_u8().await.unwrap(), vs2::NACK);
+
+    client.write_all(&[vs2::LEADIN, 0x05, 0x00, 0x01, 0x00, 0xF8, 0x08, 0x06]).await.unwrap();
+    assert_eq!(client.read_u8().await.unwrap(), vs2::ACK);
+
+    let mut response = [0; 16];
+    client.read_exact(&mut response).await.unwrap();
+    assert_eq!(response[..7], [vs2::LEADIN, 0x0D, 0x01, 0x01, 0x00, 0xF8, 0x08]);
+    assert_eq!(response[7..15], [0xff; 8]);
+  }
+}
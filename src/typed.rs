@@ -0,0 +1,333 @@
+//! Strongly typed accessors for all commands of each `Device`.
+//!
+//! Each device has its own module containing a `Device` wrapper around a `VControl`. It implements one accessor trait
+//! from `commands` per supported command, e.g. `commands::BedienteilbaGwga1::bedienteil_ba_gwga1`, and re-exports these
+//! traits anonymously, so importing the device module brings all of its methods into scope. Values with a mapping are
+//! represented as enums in `mappings`, which are named after a command using them, values with a unit are represented
+//! as `f64` newtypes in `units`. Getters return `None` if the controller reports no value.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use vcontrol::{Optolink, VControl, typed::vscot_ho1_4::{self, *}};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut vcontrol = VControl::connect(Optolink::open("/dev/ttyUSB0").await?).await?;
+//!
+//! let mut device = vscot_ho1_4::Device::new(&mut vcontrol).expect("unexpected device");
+//! if let Some(temperature) = device.tiefpass_temperaturwert_ats().await? {
+//!   println!("{temperature}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::Ipv4Addr;
+
+use crate::{
+  Error, VControl, Value,
+  types::{self, CircuitTimes, Date, DateTime, DayMonth, ScreedDrying},
+};
+
+/// Conversion from a dynamic `Value` returned by `VControl::get`.
+pub trait FromValue: Sized {
+  fn from_value(value: Value) -> Result<Self, Error>;
+}
+
+/// Conversion into a dynamic `Value` accepted by `VControl::set`.
+pub trait IntoValue {
+  fn into_value(self) -> Value;
+}
+
+fn unexpected<T>(expected: &str, value: Value) -> Result<T, Error> {
+  Err(Error::InvalidFormat(format!("expected {expected}, got {value:?}")))
+}
+
+impl FromValue for Value {
+  fn from_value(value: Value) -> Result<Self, Error> {
+    Ok(value)
+  }
+}
+
+impl IntoValue for Value {
+  fn into_value(self) -> Value {
+    self
+  }
+}
+
+impl FromValue for i64 {
+  fn from_value(value: Value) -> Result<Self, Error> {
+    match value {
+      Value::Int(n) => Ok(n),
+      value => unexpected("integer", value),
+    }
+  }
+}
+
+impl IntoValue for i64 {
+  fn into_value(self) -> Value {
+    Value::Int(self)
+  }
+}
+
+impl FromValue for f64 {
+  fn from_value(value: Value) -> Result<Self, Error> {
+    match value {
+      Value::Double(n) => Ok(n),
+      Value::Int(n) => Ok(n as f64),
+      value => unexpected("number", value),
+    }
+  }
+}
+
+impl IntoValue for f64 {
+  fn into_value(self) -> Value {
+    Value::Double(self)
+  }
+}
+
+macro_rules! impl_value {
+  ($ty:ty, $variant:ident, $expected:literal) => {
+    impl FromValue for $ty {
+      fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+          Value::$variant(v) => Ok(v),
+          value => unexpected($expected, value),
+        }
+      }
+    }
+
+    impl IntoValue for $ty {
+      fn into_value(self) -> Value {
+        Value::$variant(self)
+      }
+    }
+  };
+}
+
+impl_value!(String, String, "string");
+impl_value!(Vec<u8>, ByteArray, "byte array");
+impl_value!(Date, Date, "date");
+impl_value!(DateTime, DateTime, "date and time");
+impl_value!(DayMonth, DayMonth, "day and month");
+impl_value!(ScreedDrying, ScreedDrying, "screed drying step");
+impl_value!(Ipv4Addr, IpAddress, "IP address");
+impl_value!(types::Error, Error, "error");
+
+impl FromValue for CircuitTimes {
+  fn from_value(value: Value) -> Result<Self, Error> {
+    match value {
+      Value::CircuitTimes(circuit_times) => Ok(*circuit_times),
+      value => unexpected("circuit times", value),
+    }
+  }
+}
+
+impl IntoValue for CircuitTimes {
+  fn into_value(self) -> Value {
+    Value::CircuitTimes(Box::new(self))
+  }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+  fn from_value(value: Value) -> Result<Self, Error> {
+    match value {
+      Value::Array(values) => values.into_iter().map(T::from_value).collect(),
+      value => unexpected("array", value),
+    }
+  }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+  fn into_value(self) -> Value {
+    Value::Array(self.into_iter().map(T::into_value).collect())
+  }
+}
+
+macro_rules! unit {
+  ($name:ident, $unit:literal) => {
+    #[doc = concat!("A value in `", $unit, "`.")]
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+    pub struct $name(pub f64);
+
+    impl $name {
+      /// The unit symbol.
+      pub const UNIT: &'static str = $unit;
+    }
+
+    impl ::std::fmt::Display for $name {
+      fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{} {}", self.0, Self::UNIT)
+      }
+    }
+
+    impl $crate::typed::FromValue for $name {
+      fn from_value(value: $crate::Value) -> Result<Self, $crate::Error> {
+        <f64 as $crate::typed::FromValue>::from_value(value).map(Self)
+      }
+    }
+
+    impl $crate::typed::IntoValue for $name {
+      fn into_value(self) -> $crate::Value {
+        $crate::Value::Double(self.0)
+      }
+    }
+  };
+}
+
+macro_rules! mapping {
+  ($name:ident, $mapping:ident, { $($variant:ident = $key:literal,)* }) => {
+    #[doc = concat!("Values of `", stringify!($mapping), "`.")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[repr(i32)]
+    pub enum $name {
+      $($variant = $key,)*
+    }
+
+    impl $name {
+      /// Returns the raw value.
+      pub const fn key(self) -> i32 {
+        self as i32
+      }
+
+      /// Returns the label of the value in the given locale.
+      pub fn label(self, locale: $crate::Locale) -> &'static str {
+        $crate::mappings::$mapping.get(self.key(), locale).unwrap_or_default()
+      }
+    }
+
+    impl ::std::fmt::Display for $name {
+      fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        self.label($crate::Locale::default()).fmt(f)
+      }
+    }
+
+    impl $crate::typed::FromValue for $name {
+      fn from_value(value: $crate::Value) -> Result<Self, $crate::Error> {
+        match value {
+          $($crate::Value::Int($key) => Ok(Self::$variant),)*
+          value => Err($crate::Error::UnknownEnumVariant(format!(
+            "{:?} is not a valid value for {}", value, stringify!($name)
+          ))),
+        }
+      }
+    }
+
+    impl $crate::typed::IntoValue for $name {
+      fn into_value(self) -> $crate::Value {
+        $crate::Value::Int(self.key().into())
+      }
+    }
+  };
+}
+
+/// Access to the `VControl` of a typed `Device`, used by the accessor traits in `commands`.
+#[allow(async_fn_in_trait)]
+pub trait TypedDevice {
+  /// Returns the wrapped `VControl`.
+  fn vcontrol(&mut self) -> &mut VControl;
+
+  /// Gets the value of the given command, or `None` if the controller reports no value.
+  async fn get<T: FromValue>(&mut self, command: &str) -> Result<Option<T>, Error> {
+    match self.vcontrol().get(command).await?.value {
+      Value::Empty => Ok(None),
+      value => T::from_value(value).map(Some),
+    }
+  }
+
+  /// Sets the value of the given command.
+  async fn set<T: IntoValue>(&mut self, command: &str, value: T) -> Result<(), Error> {
+    self.vcontrol().set(command, value.into_value()).await
+  }
+}
+
+macro_rules! command {
+  ($name:ident, $command:literal $(, get = $get:ident)? $(, set = $set:ident)?) => {
+    #[doc = concat!("Accessors for `", $command, "`.")]
+    #[allow(async_fn_in_trait)]
+    pub trait $name: $crate::typed::TypedDevice {
+      $(
+        #[doc = concat!("Gets the value of `", $command, "`.")]
+        async fn $get(&mut self) -> Result<Option<super::values::$name>, $crate::Error> {
+          $crate::typed::TypedDevice::get(self, $command).await
+        }
+      )?
+
+      $(
+        #[doc = concat!("Sets the value of `", $command, "`.")]
+        async fn $set(&mut self, value: super::values::$name) -> Result<(), $crate::Error> {
+          $crate::typed::TypedDevice::set(self, $command, value).await
+        }
+      )?
+    }
+  };
+}
+
+macro_rules! device {
+  ($name:literal, { $($command:ident,)* }) => {
+    pub use super::values::{$($command),*};
+    pub use super::commands::{$($command as _),*};
+
+    #[doc = concat!("Typed commands of `", $name, "`.")]
+    #[derive(Debug)]
+    pub struct Device<'a> {
+      vcontrol: &'a mut $crate::VControl,
+    }
+
+    impl<'a> Device<'a> {
+      /// The name of the device.
+      pub const NAME: &'static str = $name;
+
+      /// Wraps the given `VControl`, returning `None` if it is connected to a different device.
+      pub fn new(vcontrol: &'a mut $crate::VControl) -> Option<Self> {
+        (vcontrol.device().name() == Self::NAME).then_some(Self { vcontrol })
+      }
+    }
+
+    impl $crate::typed::TypedDevice for Device<'_> {
+      fn vcontrol(&mut self) -> &mut $crate::VControl {
+        self.vcontrol
+      }
+    }
+
+    $(impl super::commands::$command for Device<'_> {})*
+  };
+}
+
+include!(concat!(env!("OUT_DIR"), "/typed.rs"));
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    mappings::BedienteilBAGWGA1,
+    units::DegreesCelsius,
+    vscot_ho1_70::{self, *},
+  };
+  use crate::{
+    Device, VControl,
+    sim::{Simulator, connect},
+    types::DeviceId,
+  };
+
+  #[tokio::test]
+  async fn typed_accessors() {
+    let device_id = DeviceId::from_bytes(&[0x20, 0xCB, 0x01, 0x46, 0x00, 0x00, 0x00, 0x00]);
+    let simulator = Simulator::from_device(Device::detect(device_id, None).unwrap());
+    simulator.write(0x5525, &(-35i16).to_le_bytes());
+    simulator.write(0x2323, &[2]);
+    // `Kesselsoll_eff` has no value.
+    simulator.write(0x555A, &[0xff, 0xff]);
+
+    let mut vcontrol = VControl::connect(connect(simulator.clone()).await).await.unwrap();
+    let mut device = vscot_ho1_70::Device::new(&mut vcontrol).unwrap();
+
+    assert_eq!(device.tiefpass_temperaturwert_ats().await.unwrap(), Some(DegreesCelsius(-3.5)));
+    assert_eq!(device.bedienteil_ba_gwga1().await.unwrap(), Some(BedienteilBAGWGA1::HeatingPlusDhw));
+    assert_eq!(device.kesselsoll_eff().await.unwrap(), None);
+
+    device.set_bedienteil_ba_gwga1(BedienteilBAGWGA1::Standby).await.unwrap();
+    let mut byte = [0xff];
+    simulator.read(0x2323, &mut byte);
+    assert_eq!(byte, [0]);
+  }
+}
@@ -1,12 +1,26 @@
 use core::{fmt, str::FromStr};
 
 use arrayref::array_ref;
+use chrono::Weekday;
 #[cfg(feature = "schemars")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 
+use crate::Error;
+
+/// The maximum number of time spans per day.
+const MAX_SPANS: usize = 4;
+
+fn invalid(message: String) -> Error {
+  Error::InvalidFormat(message)
+}
+
+/// A weekly schedule, consisting of a `CircuitTime` per day.
+///
+/// The text format lists days with the same time spans together, e.g.
+/// `mon-fri 06:00-08:00,17:00-22:00; sat,sun 08:00-23:00`. Days which are not listed are off.
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircuitTimes {
   mon: CircuitTime,
   tue: CircuitTime,
@@ -51,13 +65,206 @@ impl CircuitTimes {
       sun[0], sun[1], sun[2], sun[3], sun[4], sun[5], sun[6], sun[7],
     ]
   }
+
+  /// Returns the time spans of the given day.
+  pub fn day(&self, day: Weekday) -> &CircuitTime {
+    match day {
+      Weekday::Mon => &self.mon,
+      Weekday::Tue => &self.tue,
+      Weekday::Wed => &self.wed,
+      Weekday::Thu => &self.thu,
+      Weekday::Fri => &self.fri,
+      Weekday::Sat => &self.sat,
+      Weekday::Sun => &self.sun,
+    }
+  }
+
+  fn day_mut(&mut self, day: Weekday) -> &mut CircuitTime {
+    match day {
+      Weekday::Mon => &mut self.mon,
+      Weekday::Tue => &mut self.tue,
+      Weekday::Wed => &mut self.wed,
+      Weekday::Thu => &mut self.thu,
+      Weekday::Fri => &mut self.fri,
+      Weekday::Sat => &mut self.sat,
+      Weekday::Sun => &mut self.sun,
+    }
+  }
+
+  /// Sets the time spans of the given day.
+  pub fn set_day(&mut self, day: Weekday, circuit_time: CircuitTime) {
+    *self.day_mut(day) = circuit_time;
+  }
+
+  /// Copies the time spans of one day to another.
+  pub fn copy_day(&mut self, from: Weekday, to: Weekday) {
+    let circuit_time = self.day(from).clone();
+    self.set_day(to, circuit_time);
+  }
+
+  /// Removes all time spans of the given day.
+  pub fn clear_day(&mut self, day: Weekday) {
+    self.set_day(day, CircuitTime::default());
+  }
+
+  /// Removes all time spans of all days.
+  pub fn clear(&mut self) {
+    *self = Self::default();
+  }
+
+  /// Sets the days listed in the given text, e.g. `sat,sun 08:00-23:00`, leaving all other days unchanged.
+  pub fn update(&mut self, s: &str) -> Result<(), Error> {
+    let mut days = Vec::new();
+
+    for part in s.split(';').map(str::trim).filter(|part| !part.is_empty()) {
+      let (part_days, circuit_time) =
+        part.split_once(char::is_whitespace).ok_or_else(|| invalid(format!("missing time spans in “{part}”")))?;
+      let circuit_time = circuit_time.parse::<CircuitTime>()?;
+
+      for day in parse_days(part_days)? {
+        if days.iter().any(|(d, _)| *d == day) {
+          return Err(invalid(format!("{} is specified more than once", day_name(day))));
+        }
+
+        days.push((day, circuit_time.clone()));
+      }
+    }
+
+    for (day, circuit_time) in days {
+      self.set_day(day, circuit_time);
+    }
+
+    Ok(())
+  }
 }
 
+const WEEKDAYS: [Weekday; 7] =
+  [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+fn day_name(day: Weekday) -> String {
+  day.to_string().to_lowercase()
+}
+
+/// Parses a list of days, e.g. `mon-fri,sun`.
+fn parse_days(s: &str) -> Result<Vec<Weekday>, Error> {
+  let parse_day = |s: &str| s.trim().parse::<Weekday>().map_err(|_| invalid(format!("invalid day “{}”", s.trim())));
+
+  let mut days = Vec::new();
+
+  for item in s.split(',') {
+    if let Some((from, to)) = item.split_once('-') {
+      let from = parse_day(from)?.num_days_from_monday() as usize;
+      let to = parse_day(to)?.num_days_from_monday() as usize;
+
+      if from > to {
+        return Err(invalid(format!("invalid day range “{}”", item.trim())));
+      }
+
+      days.extend(&WEEKDAYS[from..=to]);
+    } else {
+      days.push(parse_day(item)?);
+    }
+  }
+
+  Ok(days)
+}
+
+/// Formats a list of days, collapsing three or more consecutive days into a range, e.g. `mon-fri,sun`.
+fn format_days(days: &[Weekday]) -> String {
+  let mut items = Vec::new();
+
+  let mut i = 0;
+  while i < days.len() {
+    let mut j = i;
+    while j + 1 < days.len() && days[j + 1].num_days_from_monday() == days[j].num_days_from_monday() + 1 {
+      j += 1;
+    }
+
+    if j - i >= 2 {
+      items.push(format!("{}-{}", day_name(days[i]), day_name(days[j])));
+    } else {
+      items.extend(days[i..=j].iter().map(|&day| day_name(day)));
+    }
+
+    i = j + 1;
+  }
+
+  items.join(",")
+}
+
+impl fmt::Display for CircuitTimes {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut groups = Vec::<(&CircuitTime, Vec<Weekday>)>::new();
+
+    for day in WEEKDAYS {
+      let circuit_time = self.day(day);
+
+      if let Some((_, days)) = groups.iter_mut().find(|(c, _)| *c == circuit_time) {
+        days.push(day);
+      } else {
+        groups.push((circuit_time, vec![day]));
+      }
+    }
+
+    for (i, (circuit_time, days)) in groups.into_iter().enumerate() {
+      if i > 0 {
+        write!(f, "; ")?;
+      }
+
+      write!(f, "{} {}", format_days(&days), circuit_time)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl FromStr for CircuitTimes {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut circuit_times = Self::default();
+    circuit_times.update(s)?;
+    Ok(circuit_times)
+  }
+}
+
+/// The time spans of a single day, e.g. `06:00-08:00,17:00-22:00`, or `off` if there are none.
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
-pub struct CircuitTime([Option<TimeSpan>; 4]);
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitTime([Option<TimeSpan>; MAX_SPANS]);
 
 impl CircuitTime {
+  /// Creates a day from the given time spans.
+  ///
+  /// The time spans are sorted and adjacent ones are merged. Fails if time spans overlap
+  /// or if there are more than four of them.
+  pub fn new(spans: impl IntoIterator<Item = TimeSpan>) -> Result<Self, Error> {
+    let mut spans = spans.into_iter().collect::<Vec<_>>();
+    spans.sort_by_key(|span| span.from);
+
+    let mut merged = Vec::<TimeSpan>::new();
+    for span in spans {
+      match merged.last_mut() {
+        Some(last) if last.to > span.from => {
+          return Err(invalid(format!("time span {last} overlaps with {span}")));
+        },
+        Some(last) if last.to == span.from => last.to = span.to,
+        _ => merged.push(span),
+      }
+    }
+
+    if merged.len() > MAX_SPANS {
+      return Err(invalid(format!("at most {MAX_SPANS} time spans are supported, got {}", merged.len())));
+    }
+
+    let mut circuit_time = Self::default();
+    for (slot, span) in circuit_time.0.iter_mut().zip(merged) {
+      *slot = Some(span);
+    }
+
+    Ok(circuit_time)
+  }
+
   pub fn from_bytes(bytes: &[u8; 8]) -> Self {
     Self([
       Time::from_byte(bytes[0]).zip(Time::from_byte(bytes[1])).map(|(from, to)| TimeSpan { from, to }),
@@ -81,29 +288,48 @@ impl CircuitTime {
       timespan4[0], timespan4[1],
     ]
   }
+
+  /// Returns the time spans.
+  pub fn spans(&self) -> impl Iterator<Item = TimeSpan> + '_ {
+    self.0.iter().flatten().copied()
+  }
+
+  /// Returns whether there are no time spans.
+  pub fn is_empty(&self) -> bool {
+    self.spans().next().is_none()
+  }
 }
 
 impl fmt::Display for CircuitTime {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    let mut comma = false;
-    for timespan in self.0 {
-      if comma {
-        write!(f, ", ")?;
-      }
+    if self.is_empty() {
+      return write!(f, "off");
+    }
 
-      if let Some(timespan) = timespan {
-        write!(f, "{}", timespan)?;
-      } else {
-        write!(f, "--:-- – --:--")?;
+    for (i, span) in self.spans().enumerate() {
+      if i > 0 {
+        write!(f, ",")?;
       }
 
-      comma = true;
+      write!(f, "{}", span)?;
     }
 
     Ok(())
   }
 }
 
+impl FromStr for CircuitTime {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.trim().eq_ignore_ascii_case("off") {
+      return Ok(Self::default());
+    }
+
+    Self::new(s.split(',').map(str::parse).collect::<Result<Vec<TimeSpan>, _>>()?)
+  }
+}
+
 impl fmt::Debug for CircuitTime {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "CircuitTime(")?;
@@ -112,14 +338,34 @@ impl fmt::Debug for CircuitTime {
   }
 }
 
+/// A time span within a day, e.g. `06:00-08:00`.
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-struct TimeSpan {
+pub struct TimeSpan {
   from: Time,
   to: Time,
 }
 
 impl TimeSpan {
+  /// Creates a time span, failing if it does not end after it starts.
+  pub fn new(from: Time, to: Time) -> Result<Self, Error> {
+    if from >= to {
+      return Err(invalid(format!("time span {from}-{to} does not end after it starts")));
+    }
+
+    Ok(Self { from, to })
+  }
+
+  /// Returns the start of the time span.
+  pub fn from(&self) -> Time {
+    self.from
+  }
+
+  /// Returns the end of the time span.
+  pub fn to(&self) -> Time {
+    self.to
+  }
+
   pub fn to_bytes(self) -> [u8; 2] {
     [self.from.to_byte(), self.to.to_byte()]
   }
@@ -127,18 +373,64 @@ impl TimeSpan {
 
 impl fmt::Display for TimeSpan {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{} – {}", self.from, self.to)
+    write!(f, "{}-{}", self.from, self.to)
   }
 }
 
+impl FromStr for TimeSpan {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    let (from, to) = s.split_once(['-', '–']).ok_or_else(|| invalid(format!("invalid time span “{s}”")))?;
+
+    let parse_time =
+      |t: &str| t.trim().parse::<Time>().map_err(|err| invalid(format!("invalid time “{}”: {err}", t.trim())));
+
+    Self::new(parse_time(from)?, parse_time(to)?)
+  }
+}
+
+/// A time of day in steps of 10 minutes, from `00:00` up to and including `24:00`.
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Time {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Time {
   hour: u8,
   minute: u8,
 }
 
 impl Time {
+  /// Creates a time, failing if it is out of range or not a multiple of 10 minutes.
+  pub fn new(hour: u8, minute: u8) -> Result<Self, Error> {
+    Self::validate(hour, minute).map_err(|err| invalid(err.to_owned()))
+  }
+
+  fn validate(hour: u8, minute: u8) -> Result<Self, &'static str> {
+    if hour > 24 || (hour == 24 && minute > 0) {
+      return Err("hour out of range");
+    }
+
+    if minute >= 60 {
+      return Err("minute out of range");
+    }
+
+    if !minute.is_multiple_of(10) {
+      return Err("minute is not a multiple of 10");
+    }
+
+    Ok(Self { hour, minute })
+  }
+
+  /// Returns the hour.
+  pub fn hour(&self) -> u8 {
+    self.hour
+  }
+
+  /// Returns the minute.
+  pub fn minute(&self) -> u8 {
+    self.minute
+  }
+
   pub const fn from_byte(byte: u8) -> Option<Self> {
     match byte {
       0xff => None,
@@ -185,19 +477,11 @@ impl FromStr for Time {
     let m1 = chars.next().and_then(char_to_u8).ok_or("first minute character is not a number")?;
     let m2 = chars.next().and_then(char_to_u8).ok_or("second minute character is not a number")?;
 
-    let hour = h1 * 10 + h2;
-
-    if hour > 24 {
-      return Err("hour out of range");
+    if chars.next().is_some() {
+      return Err("unexpected characters after minute");
     }
 
-    let minute = m1 * 10 + m2;
-
-    if minute >= 60 {
-      return Err("minute out of range");
-    }
-
-    Ok(Time { hour, minute })
+    Self::validate(h1 * 10 + h2, m1 * 10 + m2)
   }
 }
 
@@ -228,4 +512,51 @@ mod tests {
     let time = serde_json::from_str::<Time>(s).unwrap();
     assert_eq!(time, Time { hour: 24, minute: 0 });
   }
+
+  #[test]
+  fn circuit_times_round_trip() {
+    let s = "mon-fri 06:00-08:00,17:00-22:00; sat,sun 08:00-23:00";
+
+    let circuit_times = s.parse::<CircuitTimes>().unwrap();
+    assert_eq!(circuit_times.to_string(), s);
+    assert_eq!(CircuitTimes::from_bytes(&circuit_times.to_bytes()), circuit_times);
+    assert_eq!(CircuitTimes::default().to_string(), "mon-sun off");
+  }
+
+  #[test]
+  fn circuit_times_normalisation() {
+    let circuit_times =
+      "sun,sat 08:00-12:00,12:00-23:00; Monday 17:00-22:00, 06:00-08:00".parse::<CircuitTimes>().unwrap();
+    assert_eq!(circuit_times.to_string(), "mon 06:00-08:00,17:00-22:00; tue-fri off; sat,sun 08:00-23:00");
+  }
+
+  #[test]
+  fn circuit_times_validation() {
+    assert!("mon 06:05-08:00".parse::<CircuitTimes>().is_err());
+    assert!("mon 08:00-06:00".parse::<CircuitTimes>().is_err());
+    assert!("mon 06:00-08:00,07:00-09:00".parse::<CircuitTimes>().is_err());
+    assert!("mon 01:00-02:00,03:00-04:00,05:00-06:00,07:00-08:00,09:00-10:00".parse::<CircuitTimes>().is_err());
+    assert!("mon 24:10-24:20".parse::<CircuitTimes>().is_err());
+    assert!("mon-fri 06:00-08:00; fri off".parse::<CircuitTimes>().is_err());
+    assert!("fri-mon off".parse::<CircuitTimes>().is_err());
+    assert!("mon".parse::<CircuitTimes>().is_err());
+  }
+
+  #[test]
+  fn circuit_times_editing() {
+    let mut circuit_times = "mon-fri 06:00-08:00".parse::<CircuitTimes>().unwrap();
+
+    circuit_times.update("wed 12:00-13:00").unwrap();
+    assert_eq!(circuit_times.to_string(), "mon,tue,thu,fri 06:00-08:00; wed 12:00-13:00; sat,sun off");
+
+    circuit_times.copy_day(Weekday::Wed, Weekday::Sun);
+    circuit_times.clear_day(Weekday::Mon);
+    assert_eq!(circuit_times.to_string(), "mon,sat off; tue,thu,fri 06:00-08:00; wed,sun 12:00-13:00");
+
+    assert!(circuit_times.update("sat 10:00-09:00").is_err());
+    assert_eq!(circuit_times.day(Weekday::Sat), &CircuitTime::default());
+
+    circuit_times.clear();
+    assert_eq!(circuit_times, CircuitTimes::default());
+  }
 }
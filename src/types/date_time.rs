@@ -133,6 +133,94 @@ impl fmt::Debug for Date {
   }
 }
 
+/// A recurring date consisting of only a day and a month, e.g. for holiday programs.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DayMonth {
+  month: u8,
+  day: u8,
+}
+
+impl DayMonth {
+  /// Creates a new day and month, allowing February 29th.
+  pub fn new(month: u8, day: u8) -> Option<Self> {
+    NaiveDate::from_ymd_opt(2000, month.into(), day.into())?;
+    Some(Self { month, day })
+  }
+
+  /// Parses the month and day from the BCD-encoded date-time layout, ignoring the year and time.
+  pub fn from_bytes(bytes: &[u8; 8]) -> Result<Self, Error> {
+    let month = bcd_to_dec(bytes[2]);
+    let day = bcd_to_dec(bytes[3]);
+
+    Self::new(month, day).ok_or_else(|| Error::InvalidFormat(format!("invalid day and month: --{month:02}-{day:02}")))
+  }
+
+  /// Returns the bytes in the BCD-encoded date-time layout, with the year and time set to zero.
+  pub fn to_bytes(&self) -> [u8; 8] {
+    [0, 0, dec_to_bcd(self.month), dec_to_bcd(self.day), 0, 0, 0, 0]
+  }
+
+  /// Returns the month.
+  pub fn month(&self) -> u8 {
+    self.month
+  }
+
+  /// Returns the day of the month.
+  pub fn day(&self) -> u8 {
+    self.day
+  }
+}
+
+impl FromStr for DayMonth {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (month, day) = s.strip_prefix("--").ok_or(())?.split_once('-').ok_or(())?;
+    Self::new(month.parse().map_err(|_| ())?, day.parse().map_err(|_| ())?).ok_or(())
+  }
+}
+
+impl<'de> Deserialize<'de> for DayMonth {
+  fn deserialize<D>(deserializer: D) -> Result<DayMonth, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(|()| serde::de::Error::custom(format!("invalid day and month: {s}")))
+  }
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for DayMonth {
+  fn schema_name() -> String {
+    "DayMonth".into()
+  }
+
+  fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+    let mut schema = generator.subschema_for::<String>().into_object();
+    schema.string().pattern = Some("^--[0-9]{2}-[0-9]{2}$".into());
+    schema.into()
+  }
+}
+
+impl Serialize for DayMonth {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.to_string())
+  }
+}
+
+impl fmt::Display for DayMonth {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "--{:02}-{:02}", self.month, self.day)
+  }
+}
+
+impl fmt::Debug for DayMonth {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "DayMonth({})", self)
+  }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub struct DateTime(pub(crate) NaiveDateTime);
 
@@ -342,6 +430,18 @@ mod tests {
     assert_eq!(time.second(), 14);
   }
 
+  #[test]
+  fn day_month() {
+    let day_month = DayMonth::from_bytes(&[0x20, 0x24, 0x12, 0x24, 0x01, 0x00, 0x00, 0x00]).unwrap();
+    assert_eq!((day_month.month(), day_month.day()), (12, 24));
+    assert_eq!(day_month.to_string(), "--12-24");
+    assert_eq!("--12-24".parse(), Ok(day_month));
+    assert_eq!(day_month.to_bytes(), [0x00, 0x00, 0x12, 0x24, 0x00, 0x00, 0x00, 0x00]);
+
+    assert!(DayMonth::new(2, 29).is_some());
+    assert!(DayMonth::new(2, 30).is_none());
+  }
+
   #[test]
   fn to_bytes() {
     let time = DateTime::new(2018, 12, 23, 17, 49, 31).unwrap();
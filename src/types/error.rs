@@ -6,7 +6,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::DateTime;
-use crate::Device;
+use crate::{Device, Locale};
 
 #[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, PartialEq, Deserialize, Serialize)]
@@ -24,8 +24,8 @@ impl Error {
     self.index
   }
 
-  pub fn to_str(&self, device: &Device) -> Option<&'static str> {
-    device.errors().get(&(self.index as i32)).cloned()
+  pub fn to_str(&self, device: &Device, locale: Locale) -> Option<&'static str> {
+    device.errors(locale).get(&(self.index as i32)).cloned()
   }
 
   pub fn time(&self) -> Option<&DateTime> {
@@ -83,7 +83,7 @@ mod tests {
   #[test]
   fn new() {
     let time = DateTime::new(2018, 12, 23, 17, 49, 31).unwrap();
-    let error = Error::new(0xAC, time.clone());
+    let error = Error::new(0xAC, time);
 
     assert_eq!(error.index, 0xAC);
     assert_eq!(error.time.as_ref().unwrap().0.year(), 2018);
@@ -2,10 +2,13 @@ mod device_id;
 pub use self::device_id::{DeviceId, DeviceIdF0};
 
 mod circuit_time;
-pub use self::circuit_time::{CircuitTime, CircuitTimes};
+pub use self::circuit_time::{CircuitTime, CircuitTimes, Time, TimeSpan};
 
 mod error;
 pub use self::error::Error;
 
 mod date_time;
-pub use self::date_time::{Date, DateTime};
+pub use self::date_time::{Date, DateTime, DayMonth};
+
+mod screed_drying;
+pub use self::screed_drying::ScreedDrying;
@@ -0,0 +1,92 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+use serde::{
+  de::{Deserialize, Deserializer},
+  ser::{Serialize, Serializer},
+};
+
+/// The current step of a screed drying (Estrich) program, which runs one temperature profile step per day.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ScreedDrying {
+  /// No screed drying program is running.
+  Off,
+  /// The screed drying program is running on the given day, starting at 1.
+  Day(u8),
+}
+
+impl ScreedDrying {
+  /// Decodes the step byte, where `0` means the program is not running.
+  pub fn from_byte(byte: u8) -> Self {
+    match byte {
+      0 => Self::Off,
+      day => Self::Day(day),
+    }
+  }
+
+  /// Returns the step byte.
+  pub fn to_byte(&self) -> u8 {
+    match *self {
+      Self::Off => 0,
+      Self::Day(day) => day,
+    }
+  }
+}
+
+impl FromStr for ScreedDrying {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.eq_ignore_ascii_case("off") {
+      return Ok(Self::Off);
+    }
+
+    let day = s.strip_prefix("day ").unwrap_or(s).parse::<u8>().map_err(|_| ())?;
+    Ok(Self::from_byte(day))
+  }
+}
+
+impl<'de> Deserialize<'de> for ScreedDrying {
+  fn deserialize<D>(deserializer: D) -> Result<ScreedDrying, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(|()| serde::de::Error::custom(format!("invalid screed drying step: {s}")))
+  }
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for ScreedDrying {
+  fn schema_name() -> String {
+    "ScreedDrying".into()
+  }
+
+  fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+    let mut schema = generator.subschema_for::<String>().into_object();
+    schema.string().pattern = Some("^(off|day [0-9]{1,3})$".into());
+    schema.into()
+  }
+}
+
+impl Serialize for ScreedDrying {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.to_string())
+  }
+}
+
+impl fmt::Display for ScreedDrying {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Off => write!(f, "off"),
+      Self::Day(day) => write!(f, "day {day}"),
+    }
+  }
+}
+
+impl fmt::Debug for ScreedDrying {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "ScreedDrying({})", self)
+  }
+}
@@ -1,11 +1,17 @@
 use core::convert::Infallible;
-use std::{fmt, str::FromStr};
+use std::{fmt, net::Ipv4Addr, str::FromStr};
 
+use arrayref::array_ref;
+use chrono::{Days, NaiveDate};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  Command, Device, Locale, Mapping,
   conversion::Conversion,
-  types::{CircuitTimes, Date, DateTime, DeviceId, DeviceIdF0, Error},
+  types::{CircuitTimes, Date, DateTime, DayMonth, DeviceId, DeviceIdF0, Error, ScreedDrying},
 };
 
 #[derive(Debug, Clone)]
@@ -20,6 +26,7 @@ impl fmt::Display for ConversionError<'_> {
   }
 }
 
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Value {
@@ -34,11 +41,20 @@ pub enum Value {
   String(String),
   Date(Date),
   DateTime(DateTime),
+  DayMonth(DayMonth),
+  ScreedDrying(ScreedDrying),
+  IpAddress(Ipv4Addr),
   CircuitTimes(Box<CircuitTimes>),
   Error(Error),
   Empty,
 }
 
+/// Dates are counted in days since the Unix epoch.
+const DAY_TO_DATE_EPOCH: NaiveDate = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+/// Service intervals are counted in seconds and shown in months, i.e. a twelfth of a 365.25-day year.
+const SECONDS_PER_MONTH: f64 = 365.25 * 24.0 * 3600.0 / 12.0;
+
 macro_rules! convert_double {
   ($value:expr, $op:tt, $n:literal) => {
     if let Value::Double(n) = $value {
@@ -65,18 +81,74 @@ impl Value {
         }
       },
       Conversion::SecToMinute => convert_double!(self, /, 60.0),
-      Conversion::SecToHour => convert_double!(self, /, 3600.0),
+      Conversion::SecToHour | Conversion::LastBurnerCheck => convert_double!(self, /, 3600.0),
       Conversion::HexByteToAsciiByte => {
-        if let Value::ByteArray(bytes) = self {
-          let s = bytes.iter().filter(|b| **b != b'0').map(|b| char::from(*b)).collect::<String>();
+        if let Value::ByteArray(ref bytes) = self {
+          let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+          let s = bytes[..end].iter().map(|&b| char::from(b)).collect::<String>();
           return Ok(Value::String(s));
         }
       },
-      Conversion::HexByteToVersion => {
+      Conversion::HexByteToVersion | Conversion::HexByteToDecimalByte => {
         if let Value::ByteArray(bytes) = self {
           return Ok(Value::String(bytes.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(".")));
         }
       },
+      Conversion::HexByteToUtf16Byte => {
+        if let Value::ByteArray(ref bytes) = self {
+          let units = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).take_while(|&u| u != 0);
+          if let Ok(s) = char::decode_utf16(units).collect::<Result<String, _>>() {
+            return Ok(Value::String(s));
+          }
+        }
+      },
+      Conversion::FixedStringTerminalZeroes => {
+        if let Value::ByteArray(ref bytes) = self {
+          let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+          if let Ok(s) = String::from_utf8(bytes[..end].to_vec()) {
+            return Ok(Value::String(s));
+          }
+        }
+      },
+      Conversion::DayMonthBcd => {
+        if let Value::ByteArray(ref bytes) = self
+          && bytes.len() == 8
+          && let Ok(day_month) = DayMonth::from_bytes(array_ref![bytes, 0, 8])
+        {
+          return Ok(Value::DayMonth(day_month));
+        }
+      },
+      Conversion::DayToDate => {
+        if let Value::ByteArray(ref bytes) = self
+          && bytes.len() == 2
+        {
+          let days = u16::from_le_bytes([bytes[0], bytes[1]]);
+          return Ok(Value::Date(Date(DAY_TO_DATE_EPOCH + Days::new(days.into()))));
+        }
+      },
+      Conversion::Estrich => match self {
+        Value::ByteArray(ref bytes) if bytes.len() == 1 => {
+          return Ok(Value::ScreedDrying(ScreedDrying::from_byte(bytes[0])));
+        },
+        Value::Int(n) => {
+          if let Ok(byte) = u8::try_from(n) {
+            return Ok(Value::ScreedDrying(ScreedDrying::from_byte(byte)));
+          }
+        },
+        _ => (),
+      },
+      Conversion::LastCheckInterval => {
+        if let Value::Double(n) = self {
+          return Ok(Value::Double(n / SECONDS_PER_MONTH));
+        }
+      },
+      Conversion::IpAddress => {
+        if let Value::ByteArray(ref bytes) = self
+          && bytes.len() == 4
+        {
+          return Ok(Value::IpAddress(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])));
+        }
+      },
       Conversion::RotateBytes => match self {
         Value::ByteArray(ref mut array) => {
           array.reverse();
@@ -85,7 +157,6 @@ impl Value {
         Value::Int(n) => return Ok(Value::Int(n)),
         _ => (),
       },
-      _ => (),
     }
 
     Err(ConversionError { value: self, conversion })
@@ -105,6 +176,7 @@ impl Value {
     Ok(Self::Double(convert(value)))
   }
 
+  /// Reverses `convert`, returning raw bytes for conversions which operate on bytes.
   pub(crate) fn convert_back(self, conversion: &Conversion) -> Result<Self, ConversionError<'_>> {
     match conversion {
       Conversion::Div2 => self.convert_number(|v| v * 2.0, conversion),
@@ -117,31 +189,131 @@ impl Value {
       Conversion::Mul10 => self.convert_number(|v| v / 10.0, conversion),
       Conversion::Mul100 => self.convert_number(|v| v / 100.0, conversion),
       Conversion::MulOffset { factor, offset } => self.convert_number(|v| (v - offset) / factor, conversion),
-      _ => Err(ConversionError { value: self, conversion }),
+      Conversion::SecToMinute => self.convert_number(|v| v * 60.0, conversion),
+      Conversion::SecToHour | Conversion::LastBurnerCheck => self.convert_number(|v| v * 3600.0, conversion),
+      Conversion::HexByteToAsciiByte => match self {
+        Value::String(s) if s.is_ascii() => Ok(Value::ByteArray(s.into_bytes())),
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::FixedStringTerminalZeroes => match self {
+        Value::String(s) => Ok(Value::ByteArray(s.into_bytes())),
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::HexByteToUtf16Byte => match self {
+        Value::String(ref s) => Ok(Value::ByteArray(s.encode_utf16().flat_map(u16::to_le_bytes).collect())),
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::HexByteToVersion | Conversion::HexByteToDecimalByte => match self {
+        Value::String(ref s) => match s.split('.').map(str::parse).collect::<Result<Vec<u8>, _>>() {
+          Ok(bytes) => Ok(Value::ByteArray(bytes)),
+          Err(_) => Err(ConversionError { value: self, conversion }),
+        },
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::DayMonthBcd => match self {
+        Value::DayMonth(day_month) => Ok(Value::ByteArray(day_month.to_bytes().to_vec())),
+        Value::String(ref s) => match s.parse::<DayMonth>() {
+          Ok(day_month) => Ok(Value::ByteArray(day_month.to_bytes().to_vec())),
+          Err(()) => Err(ConversionError { value: self, conversion }),
+        },
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::DayToDate => {
+        let date = match self {
+          Value::Date(Date(date)) => Some(date),
+          Value::String(ref s) => s.parse::<Date>().ok().map(|Date(date)| date),
+          _ => None,
+        };
+
+        match date.and_then(|date| u16::try_from((date - DAY_TO_DATE_EPOCH).num_days()).ok()) {
+          Some(days) => Ok(Value::ByteArray(days.to_le_bytes().to_vec())),
+          None => Err(ConversionError { value: self, conversion }),
+        }
+      },
+      Conversion::Estrich => match self {
+        Value::ScreedDrying(screed_drying) => Ok(Value::ByteArray(vec![screed_drying.to_byte()])),
+        Value::Int(n) => match u8::try_from(n) {
+          Ok(byte) => Ok(Value::ByteArray(vec![byte])),
+          Err(_) => Err(ConversionError { value: self, conversion }),
+        },
+        Value::String(ref s) => match s.parse::<ScreedDrying>() {
+          Ok(screed_drying) => Ok(Value::ByteArray(vec![screed_drying.to_byte()])),
+          Err(()) => Err(ConversionError { value: self, conversion }),
+        },
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      Conversion::LastCheckInterval => self.convert_number(|v| v * SECONDS_PER_MONTH, conversion),
+      Conversion::IpAddress => match self {
+        Value::IpAddress(ip_address) => Ok(Value::ByteArray(ip_address.octets().to_vec())),
+        Value::String(ref s) => match s.parse::<Ipv4Addr>() {
+          Ok(ip_address) => Ok(Value::ByteArray(ip_address.octets().to_vec())),
+          Err(_) => Err(ConversionError { value: self, conversion }),
+        },
+        _ => Err(ConversionError { value: self, conversion }),
+      },
+      // Reversing the bytes is its own inverse.
+      Conversion::RotateBytes => self.convert(conversion),
     }
   }
 }
 
-#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Serialize)]
 pub struct OutputValue {
   pub value: Value,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub unit: Option<&'static str>,
   #[serde(skip_serializing_if = "Option::is_none")]
-  pub mapping: Option<&'static phf::map::Map<i32, &'static str>>,
+  pub stepping: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "schemars", schemars(with = "Option<std::collections::BTreeMap<i32, String>>"))]
+  pub mapping: Option<&'static Mapping>,
+}
+
+impl OutputValue {
+  /// Wraps a value of the given command, using the error mapping of `device` for `Value::Error`.
+  pub(crate) fn from_command(command: &'static Command, device: Option<&'static Device>, value: Value) -> Self {
+    let mapping = if let Value::Error(ref _error) = value {
+      device.map(Device::error_mapping)
+    } else if let Some(ref mapping) = command.mapping {
+      Some(mapping)
+    } else {
+      None
+    };
+
+    Self { value, unit: command.unit, stepping: command.stepping, mapping }
+  }
+
+  /// Returns an object which displays the value using mapping labels in the given locale.
+  pub fn display(&self, locale: Locale) -> impl fmt::Display + '_ {
+    Localized { output_value: self, locale }
+  }
 }
 
 impl fmt::Display for OutputValue {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match &self.value {
+    self.display(Locale::default()).fmt(f)
+  }
+}
+
+struct Localized<'a> {
+  output_value: &'a OutputValue,
+  locale: Locale,
+}
+
+impl fmt::Display for Localized<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let Self { output_value: this, locale } = *self;
+
+    match &this.value {
       Value::DeviceId(device_id) => write!(f, "{:#?}", device_id)?,
       Value::DeviceIdF0(device_id_f0) => write!(f, "{:#?}", device_id_f0)?,
       Value::Int(n) => {
-        if let Some(mapping) = self.mapping {
-          if let Some(mapping) = mapping.get(&(*n as i32)) {
-            write!(f, "{}", mapping)?;
+        if let Some(mapping) = this.mapping {
+          if let Some(label) = mapping.get(*n as i32, locale) {
+            write!(f, "{}", label)?;
           } else {
-            log::warn!("Missing mapping for {n} in {mapping:?}.");
+            log::warn!("Missing mapping for {n} in {:?}.", mapping.labels(locale));
             write!(f, "{}", n)?;
           }
         } else {
@@ -153,15 +325,18 @@ impl fmt::Display for OutputValue {
       Value::ByteArray(byte_array) => write!(f, "{:?}", byte_array)?,
       Value::Date(date) => write!(f, "{}", date)?,
       Value::DateTime(date_time) => write!(f, "{}", date_time)?,
+      Value::DayMonth(day_month) => write!(f, "{}", day_month)?,
+      Value::ScreedDrying(screed_drying) => write!(f, "{}", screed_drying)?,
+      Value::IpAddress(ip_address) => write!(f, "{}", ip_address)?,
       Value::Error(error) => {
-        write!(f, "{}", self.mapping.unwrap().get(&(error.index() as i32)).unwrap())?;
+        write!(f, "{}", this.mapping.unwrap().get(error.index() as i32, locale).unwrap())?;
       },
-      Value::CircuitTimes(cycle_times) => write!(f, "{:#?}", cycle_times)?,
+      Value::CircuitTimes(circuit_times) => write!(f, "{}", circuit_times)?,
       Value::String(string) => write!(f, "{}", string)?,
       Value::Empty => return Ok(()),
     }
 
-    if let Some(unit) = self.unit {
+    if let Some(unit) = this.unit {
       write!(f, " {}", unit)?;
     }
 
@@ -181,10 +356,202 @@ impl FromStr for Value {
       return Ok(Value::Double(number));
     }
 
-    // if let Ok(cycletime) = s.parse::<[CircuitTime; 4]>() {
-    //   return Ok(Value::CircuitTimes(cycletime))
-    // }
-
     Ok(Value::String(s.to_owned()))
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use proptest::prelude::*;
+
+  use super::*;
+
+  fn convert(value: Value, conversion: Conversion) -> Value {
+    value.convert(&conversion).unwrap()
+  }
+
+  #[test]
+  fn convert_ip_address() {
+    let value = convert(Value::ByteArray(vec![192, 168, 1, 42]), Conversion::IpAddress);
+    assert_eq!(value, Value::IpAddress(Ipv4Addr::new(192, 168, 1, 42)));
+    assert!(Value::ByteArray(vec![192, 168, 1]).convert(&Conversion::IpAddress).is_err());
+  }
+
+  #[test]
+  fn convert_day_month_bcd() {
+    let value =
+      convert(Value::ByteArray(vec![0x20, 0x24, 0x08, 0x15, 0x03, 0x00, 0x00, 0x00]), Conversion::DayMonthBcd);
+    assert_eq!(value, Value::DayMonth(DayMonth::new(8, 15).unwrap()));
+  }
+
+  #[test]
+  fn convert_day_to_date() {
+    let value = convert(Value::ByteArray(vec![0x00, 0x00]), Conversion::DayToDate);
+    assert_eq!(value, Value::Date(Date::new(1970, 1, 1).unwrap()));
+
+    let value = convert(Value::ByteArray(19723u16.to_le_bytes().to_vec()), Conversion::DayToDate);
+    assert_eq!(value, Value::Date(Date::new(2024, 1, 1).unwrap()));
+  }
+
+  #[test]
+  fn convert_hex_byte_to_ascii_byte() {
+    let value = convert(Value::ByteArray(b"1.0\x000\0\0".to_vec()), Conversion::HexByteToAsciiByte);
+    assert_eq!(value, Value::String("1.0\u{0}0".into()));
+  }
+
+  #[test]
+  fn convert_hex_byte_to_utf16_byte() {
+    let mut bytes = "Fußboden".encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+    bytes.resize(40, 0);
+    assert_eq!(convert(Value::ByteArray(bytes), Conversion::HexByteToUtf16Byte), Value::String("Fußboden".into()));
+  }
+
+  #[test]
+  fn convert_hex_byte_to_decimal_byte() {
+    let value = convert(Value::ByteArray(vec![1, 2, 3, 4, 5, 6, 7, 8]), Conversion::HexByteToDecimalByte);
+    assert_eq!(value, Value::String("1.2.3.4.5.6.7.8".into()));
+
+    let value = convert(Value::ByteArray(vec![0, 10, 255]), Conversion::HexByteToDecimalByte);
+    assert_eq!(value, Value::String("0.10.255".into()));
+  }
+
+  #[test]
+  fn convert_fixed_string_terminal_zeroes() {
+    let value = convert(Value::ByteArray(b"Vitodens\0\0\0\0".to_vec()), Conversion::FixedStringTerminalZeroes);
+    assert_eq!(value, Value::String("Vitodens".into()));
+  }
+
+  #[test]
+  fn convert_last_burner_check() {
+    assert_eq!(convert(Value::Double(7200.0), Conversion::LastBurnerCheck), Value::Double(2.0));
+  }
+
+  #[test]
+  fn convert_estrich() {
+    assert_eq!(convert(Value::ByteArray(vec![0]), Conversion::Estrich), Value::ScreedDrying(ScreedDrying::Off));
+    assert_eq!(convert(Value::ByteArray(vec![12]), Conversion::Estrich), Value::ScreedDrying(ScreedDrying::Day(12)));
+    assert_eq!(convert(Value::Int(3), Conversion::Estrich), Value::ScreedDrying(ScreedDrying::Day(3)));
+    assert!(Value::Int(256).convert(&Conversion::Estrich).is_err());
+
+    assert_eq!(
+      Value::ScreedDrying(ScreedDrying::Day(12)).convert_back(&Conversion::Estrich).unwrap(),
+      Value::ByteArray(vec![12])
+    );
+    assert_eq!(Value::String("off".into()).convert_back(&Conversion::Estrich).unwrap(), Value::ByteArray(vec![0]));
+    assert_eq!(Value::String("day 5".into()).convert_back(&Conversion::Estrich).unwrap(), Value::ByteArray(vec![5]));
+    assert_eq!(Value::Int(7).convert_back(&Conversion::Estrich).unwrap(), Value::ByteArray(vec![7]));
+  }
+
+  #[test]
+  fn convert_last_check_interval() {
+    // 365.25 days are 12 months.
+    assert_eq!(convert(Value::Double(31_557_600.0), Conversion::LastCheckInterval), Value::Double(12.0));
+    assert_eq!(convert(Value::Double(7_889_400.0), Conversion::LastCheckInterval), Value::Double(3.0));
+    assert_eq!(Value::Double(1.0).convert_back(&Conversion::LastCheckInterval).unwrap(), Value::Double(2_629_800.0));
+  }
+
+  fn assert_approx_eq(actual: Value, expected: f64) {
+    match actual {
+      Value::Double(n) => assert!((n - expected).abs() <= expected.abs() * 1e-9, "{n} != {expected}"),
+      value => panic!("expected Double, got {value:?}"),
+    }
+  }
+
+  fn numeric_conversions() -> impl Strategy<Value = Conversion> {
+    prop_oneof![
+      Just(Conversion::Div2),
+      Just(Conversion::Div5),
+      Just(Conversion::Div10),
+      Just(Conversion::Div100),
+      Just(Conversion::Div1000),
+      Just(Conversion::Mul2),
+      Just(Conversion::Mul5),
+      Just(Conversion::Mul10),
+      Just(Conversion::Mul100),
+      (0.001f64..1000.0, -1000f64..1000.0).prop_map(|(factor, offset)| Conversion::MulOffset { factor, offset }),
+      Just(Conversion::SecToMinute),
+      Just(Conversion::SecToHour),
+      Just(Conversion::LastBurnerCheck),
+    ]
+  }
+
+  proptest! {
+    #[test]
+    fn round_trip_numeric(conversion in numeric_conversions(), n in -1e9f64..1e9) {
+      let value = Value::Double(n).convert(&conversion).unwrap();
+      assert_approx_eq(value.convert_back(&conversion).unwrap(), n);
+    }
+
+    #[test]
+    fn round_trip_hex_byte_to_ascii_byte(s in "[\u{0}-\u{7f}]{0,15}[\u{1}-\u{7f}]") {
+      let bytes = Value::String(s.clone()).convert_back(&Conversion::HexByteToAsciiByte).unwrap();
+      prop_assert_eq!(bytes.clone().convert(&Conversion::HexByteToAsciiByte).unwrap(), Value::String(s));
+      prop_assert_eq!(bytes.clone().convert(&Conversion::HexByteToAsciiByte).unwrap().convert_back(&Conversion::HexByteToAsciiByte).unwrap(), bytes);
+    }
+
+    #[test]
+    fn round_trip_hex_byte_to_utf16_byte(s in "[^\u{0}]{0,20}") {
+      let bytes = Value::String(s.clone()).convert_back(&Conversion::HexByteToUtf16Byte).unwrap();
+      prop_assert_eq!(bytes.convert(&Conversion::HexByteToUtf16Byte).unwrap(), Value::String(s));
+    }
+
+    #[test]
+    fn round_trip_hex_byte_to_decimal_byte(bytes in prop::collection::vec(any::<u8>(), 8)) {
+      let value = Value::ByteArray(bytes.clone()).convert(&Conversion::HexByteToDecimalByte).unwrap();
+      prop_assert_eq!(value.convert_back(&Conversion::HexByteToDecimalByte).unwrap(), Value::ByteArray(bytes));
+    }
+
+    #[test]
+    fn round_trip_hex_byte_to_version(bytes in prop::collection::vec(any::<u8>(), 1..4)) {
+      let value = Value::ByteArray(bytes.clone()).convert(&Conversion::HexByteToVersion).unwrap();
+      prop_assert_eq!(value.convert_back(&Conversion::HexByteToVersion).unwrap(), Value::ByteArray(bytes));
+    }
+
+    #[test]
+    fn round_trip_fixed_string_terminal_zeroes(s in "[^\u{0}]{0,16}") {
+      let bytes = Value::String(s.clone()).convert_back(&Conversion::FixedStringTerminalZeroes).unwrap();
+      prop_assert_eq!(bytes.convert(&Conversion::FixedStringTerminalZeroes).unwrap(), Value::String(s));
+    }
+
+    #[test]
+    fn round_trip_day_month_bcd(month in 1u8..=12, day in 1u8..=31) {
+      if let Some(day_month) = DayMonth::new(month, day) {
+        let bytes = Value::DayMonth(day_month).convert_back(&Conversion::DayMonthBcd).unwrap();
+        prop_assert_eq!(bytes.clone().convert(&Conversion::DayMonthBcd).unwrap(), Value::DayMonth(day_month));
+        prop_assert_eq!(Value::String(day_month.to_string()).convert_back(&Conversion::DayMonthBcd).unwrap(), bytes);
+      }
+    }
+
+    #[test]
+    fn round_trip_day_to_date(days in any::<u16>()) {
+      let bytes = Value::ByteArray(days.to_le_bytes().to_vec());
+      let value = bytes.clone().convert(&Conversion::DayToDate).unwrap();
+      prop_assert_eq!(value.convert_back(&Conversion::DayToDate).unwrap(), bytes);
+    }
+
+    #[test]
+    fn round_trip_rotate_bytes(bytes in prop::collection::vec(any::<u8>(), 0..8)) {
+      let value = Value::ByteArray(bytes.clone()).convert(&Conversion::RotateBytes).unwrap();
+      prop_assert_eq!(value.convert_back(&Conversion::RotateBytes).unwrap(), Value::ByteArray(bytes));
+    }
+
+    #[test]
+    fn round_trip_ip_address(octets in any::<[u8; 4]>()) {
+      let value = Value::ByteArray(octets.to_vec()).convert(&Conversion::IpAddress).unwrap();
+      prop_assert_eq!(&value, &Value::IpAddress(Ipv4Addr::from(octets)));
+      prop_assert_eq!(value.convert_back(&Conversion::IpAddress).unwrap(), Value::ByteArray(octets.to_vec()));
+    }
+  }
+
+  #[test]
+  fn convert_back_unsupported() {
+    assert!(Value::String("x".into()).convert_back(&Conversion::Div10).is_err());
+    assert!(Value::String("ä".into()).convert_back(&Conversion::HexByteToAsciiByte).is_err());
+    assert!(Value::String("1a".into()).convert_back(&Conversion::HexByteToDecimalByte).is_err());
+    assert!(Value::String("1.256".into()).convert_back(&Conversion::HexByteToDecimalByte).is_err());
+    assert!(Value::String("day 256".into()).convert_back(&Conversion::Estrich).is_err());
+    assert!(Value::Int(-1).convert_back(&Conversion::Estrich).is_err());
+    assert!(Value::String("1.2.3".into()).convert_back(&Conversion::IpAddress).is_err());
+    assert!(Value::String("1969-12-31".into()).convert_back(&Conversion::DayToDate).is_err());
+  }
+}
@@ -1,4 +1,6 @@
-use crate::{Command, Device, Error, Optolink, OutputValue, Protocol, Value};
+use std::io;
+
+use crate::{Command, Device, Error, Function, Optolink, OutputValue, Protocol, ProtocolConfig, ProtocolError, Value};
 
 /// Representation of an `Optolink` connection to a specific `Device` using a specific `Protocol`.
 #[derive(Debug)]
@@ -94,11 +96,21 @@ impl VControl {
     self.protocol
   }
 
+  /// Returns the timeouts and retry limits used for the `Optolink` connection.
+  pub fn protocol_config(&self) -> ProtocolConfig {
+    self.optolink.protocol_config()
+  }
+
+  /// Sets the timeouts and retry limits used for the `Optolink` connection.
+  pub fn set_protocol_config(&mut self, config: ProtocolConfig) {
+    self.optolink.set_protocol_config(config);
+  }
+
   pub fn optolink(&mut self) -> &mut Optolink {
     &mut self.optolink
   }
 
-  fn command_by_name(&self, command: &str) -> Result<&'static Command, Error> {
+  pub(crate) fn command_by_name(&self, command: &str) -> Result<&'static Command, Error> {
     if let Some(system_command) = crate::commands::system_command(command) {
       Ok(system_command)
     } else if let Some(device_command) = self.device.command(command) {
@@ -116,17 +128,7 @@ impl VControl {
 
     self.renegotiate().await?;
     match command.get(&mut self.optolink, self.protocol).await {
-      Ok(value) => {
-        let mapping = if let Value::Error(ref _error) = value {
-          Some(self.device.errors())
-        } else if let Some(ref mapping) = command.mapping {
-          Some(mapping)
-        } else {
-          None
-        };
-
-        Ok(OutputValue { value, unit: command.unit, mapping })
-      },
+      Ok(value) => Ok(self.output_value(command, value)),
       Err(err) => {
         self.connected = false;
         Err(err)
@@ -134,6 +136,82 @@ impl VControl {
     }
   }
 
+  /// Gets the values for the given commands.
+  ///
+  /// Commands with adjacent or overlapping addresses are read together using as few requests as possible.
+  /// Requests are limited to `ProtocolConfig::max_batch_len` bytes, except for single commands which are longer than
+  /// that and are read with a request of their own. Errors for individual commands, e.g. unsupported
+  /// commands or a failed request, are returned in place of their value, while failing to renegotiate the connection
+  /// aborts the whole operation.
+  pub async fn get_many(&mut self, commands: &[&str]) -> Result<Vec<Result<OutputValue, Error>>, Error> {
+    log::trace!("VControl::get_many({commands:?})");
+
+    let mut results = Vec::with_capacity(commands.len());
+    let mut readable = vec![];
+
+    let mut non_virtual = vec![];
+
+    for (i, command) in commands.iter().enumerate() {
+      match self.command_by_name(command).and_then(|command| command.check_readable().map(|()| command)) {
+        // Only virtual reads can be batched, since addresses of other functions refer to different memory.
+        Ok(command) if command.read_function != Function::VirtualRead => {
+          non_virtual.push((i, command));
+          results.push(None);
+        },
+        Ok(command) => {
+          readable.push((i, command));
+          results.push(None);
+        },
+        Err(err) => results.push(Some(Err(err))),
+      }
+    }
+
+    let max_len = self.protocol.max_payload_len().min(self.optolink.protocol_config().max_batch_len);
+
+    for batch in batches(&readable, max_len) {
+      log::debug!("Reading {} bytes at 0x{:04X} for {} commands.", batch.len, batch.addr, batch.commands.len());
+
+      self.renegotiate().await?;
+
+      let mut buf = vec![0; batch.len];
+      if let Err(err) = self.protocol.get(&mut self.optolink, batch.addr, &mut buf).await {
+        log::warn!("Reading {} bytes at 0x{:04X} failed: {err}", batch.len, batch.addr);
+
+        // Only fail the commands of this batch, the connection is renegotiated for the next one.
+        self.connected = false;
+        for &(i, _) in &batch.commands {
+          results[i] = Some(Err(copy_io_error(&err).into()));
+        }
+
+        continue;
+      }
+
+      for (i, command) in batch.commands {
+        let offset = (command.addr - batch.addr) as usize;
+        let block = &buf[offset..(offset + command.block_len)];
+        results[i] = Some(command.deserialize_block(block).map(|value| self.output_value(command, value)));
+      }
+    }
+
+    for (i, command) in non_virtual {
+      self.renegotiate().await?;
+
+      results[i] = Some(match command.read_block(&mut self.optolink, self.protocol).await {
+        Ok(block) => command.deserialize_block(&block).map(|value| self.output_value(command, value)),
+        Err(err) => {
+          self.connected = false;
+          Err(err)
+        },
+      });
+    }
+
+    Ok(results.into_iter().map(|result| result.expect("all commands are read")).collect())
+  }
+
+  fn output_value(&self, command: &'static Command, value: Value) -> OutputValue {
+    OutputValue::from_command(command, Some(self.device), value)
+  }
+
   /// Sets the value for the given command.
   pub async fn set(&mut self, command: &str, input: Value) -> Result<(), Error> {
     log::trace!("VControl::set({command:?}, {input:?})");
@@ -149,4 +227,140 @@ impl VControl {
       },
     }
   }
+
+  /// Resets the given command to its factory default value.
+  pub async fn reset_to_default(&mut self, command: &str) -> Result<(), Error> {
+    let default_value = self
+      .command_by_name(command)?
+      .default_value()
+      .ok_or_else(|| Error::InvalidArgument(format!("{command} has no default value.")))?;
+
+    self.set(command, default_value).await
+  }
+
+  /// Sends a request with the given `function` to the address `addr`, see `Protocol::request`.
+  pub async fn request(&mut self, function: Function, addr: u16, payload: &[u8], buf: &mut [u8]) -> Result<(), Error> {
+    log::trace!("VControl::request({function:?}, 0x{addr:04X}, {payload:?})");
+
+    self.renegotiate().await?;
+    match self.protocol.request(&mut self.optolink, function, addr, payload, buf).await {
+      Ok(()) => Ok(()),
+      Err(err) => {
+        // Rejected requests are not sent, so the connection is still intact.
+        if !matches!(
+          err.kind(),
+          io::ErrorKind::PermissionDenied | io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported
+        ) {
+          self.connected = false;
+        }
+
+        Err(err.into())
+      },
+    }
+  }
+}
+
+/// Copies an `io::Error` returned by `Protocol`, keeping a contained `ProtocolError`.
+fn copy_io_error(err: &io::Error) -> io::Error {
+  match err.get_ref().and_then(|err| err.downcast_ref::<ProtocolError>()) {
+    Some(protocol_error) => io::Error::new(err.kind(), protocol_error.clone()),
+    None => io::Error::new(err.kind(), err.to_string()),
+  }
+}
+
+/// A contiguous address range which is read with a single request.
+#[derive(Debug)]
+struct Batch {
+  addr: u16,
+  len: usize,
+  commands: Vec<(usize, &'static Command)>,
+}
+
+/// Groups commands with adjacent or overlapping address ranges into batches of at most `max_len` bytes.
+///
+/// A command longer than `max_len` is put into a batch of its own.
+fn batches(commands: &[(usize, &'static Command)], max_len: usize) -> Vec<Batch> {
+  let mut commands = commands.to_vec();
+  commands.sort_by_key(|(_, command)| (command.addr, command.block_len));
+
+  let mut batches: Vec<Batch> = vec![];
+
+  for (i, command) in commands {
+    let start = command.addr as usize;
+    let end = start + command.block_len;
+
+    if let Some(batch) = batches.last_mut() {
+      let batch_start = batch.addr as usize;
+      let batch_end = end.max(batch_start + batch.len);
+
+      if start <= batch_start + batch.len && batch_end - batch_start <= max_len {
+        batch.len = batch_end - batch_start;
+        batch.commands.push((i, command));
+        continue;
+      }
+    }
+
+    batches.push(Batch { addr: command.addr, len: command.block_len, commands: vec![(i, command)] });
+  }
+
+  batches
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{AccessMode, DataType, Parameter};
+
+  const fn command(addr: u16, block_len: usize) -> Command {
+    Command {
+      addr,
+      mode: AccessMode::Read,
+      read_function: Function::VirtualRead,
+      write_function: Function::VirtualWrite,
+      data_type: DataType::Int,
+      parameter: Parameter::Byte,
+      block_count: None,
+      block_len,
+      byte_len: block_len,
+      byte_pos: 0,
+      bit_len: None,
+      bit_pos: 0,
+      conversion: None,
+      lower_bound: None,
+      upper_bound: None,
+      stepping: None,
+      unit: None,
+      mapping: None,
+      default_value: None,
+      description: None,
+      priority: None,
+      filter_criterion: None,
+      url: None,
+    }
+  }
+
+  static A: Command = command(0x2000, 2);
+  static B: Command = command(0x2002, 1);
+  static C: Command = command(0x2000, 1);
+  static D: Command = command(0x2010, 4);
+  static E: Command = command(0x2003, 8);
+
+  #[test]
+  fn batch_adjacent_and_overlapping() {
+    let batches = batches(&[(0, &D), (1, &B), (2, &A), (3, &C)], 16);
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!((batches[0].addr, batches[0].len), (0x2000, 3));
+    assert_eq!(batches[0].commands.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!((batches[1].addr, batches[1].len), (0x2010, 4));
+  }
+
+  #[test]
+  fn batch_max_len() {
+    let batches = batches(&[(0, &A), (1, &B), (2, &E)], 4);
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!((batches[0].addr, batches[0].len), (0x2000, 3));
+    assert_eq!((batches[1].addr, batches[1].len), (0x2003, 8));
+  }
 }